@@ -0,0 +1,297 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use crate::life::{BoardState, LIVE};
+use crate::pattern::state_from_cells;
+use crate::rule::Rule;
+use crate::sparse::SparseLife;
+
+// Extended Wechsler format digits, a column of 5 cells per digit. 'w' to 'z'
+// double as runs of blank columns and the strip separator.
+const DIGITS: &[u8] = b"0123456789abcdefghijklmnopqrstuvwxyz";
+
+// Strips are 5 rows tall, one bit per row in each digit.
+const STRIP_HEIGHT: i64 = 5;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseApgcodeError {
+    apgcode: String,
+    reason: String,
+}
+
+impl fmt::Display for ParseApgcodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid apgcode {:?}: {}", self.apgcode, self.reason)
+    }
+}
+
+impl std::error::Error for ParseApgcodeError {}
+
+/// Canonical apgcode of a still life ("xs4_33"), oscillator ("xp2_7") or
+/// spaceship ("xq4_153"), as used by Catagolue.
+///
+/// The pattern is run until it repeats, up to `max_period` generations. The
+/// code is the shortest, then alphabetically first, encoding over every
+/// phase and the 8 rotations and reflections. `None` if the board is empty
+/// or doesn't repeat in time.
+pub fn classify(state: &BoardState, rule: Rule, max_period: u64) -> Option<String> {
+    let mut game = SparseLife::from_state(state, rule, 0, 0);
+    let start = normalize(game.cells().copied().collect());
+    let origin = game.bounding_box()?;
+    let mut phases = vec![start.clone()];
+
+    for period in 1..=max_period {
+        game.tick();
+
+        let cells = normalize(game.cells().copied().collect());
+
+        if cells.is_empty() {
+            return None;
+        }
+
+        if cells == start {
+            let moved = game.bounding_box()? != origin;
+            let prefix = match (period, moved) {
+                (_, true) => format!("xq{}", period),
+                (1, false) => format!("xs{}", start.len()),
+                (_, false) => format!("xp{}", period),
+            };
+
+            return Some(format!("{}_{}", prefix, canonical(&phases)));
+        }
+
+        phases.push(cells);
+    }
+
+    return None;
+}
+
+/// Shortest, then alphabetically first, encoding of the phases in any
+/// orientation.
+fn canonical(phases: &[Vec<(i64, i64)>]) -> String {
+    let mut best: Option<String> = None;
+
+    for cells in phases {
+        for orientation in 0..8 {
+            let code = encode_cells(&orient(cells, orientation));
+
+            best = match best {
+                Some(b) if (b.len(), &b) <= (code.len(), &code) => Some(b),
+                _ => Some(code),
+            };
+        }
+    }
+
+    return best.unwrap_or_default();
+}
+
+/// One of the 8 rotations and reflections of a set of cells.
+fn orient(cells: &[(i64, i64)], orientation: u8) -> Vec<(i64, i64)> {
+    let cells = cells
+        .iter()
+        .map(|(x, y)| {
+            let (x, y) = if orientation & 4 != 0 {
+                (*y, *x)
+            } else {
+                (*x, *y)
+            };
+            let x = if orientation & 1 != 0 { -x } else { x };
+            let y = if orientation & 2 != 0 { -y } else { y };
+
+            (x, y)
+        })
+        .collect();
+
+    return normalize(cells);
+}
+
+/// Cells moved so their bounding box starts at (0, 0), sorted.
+fn normalize(cells: Vec<(i64, i64)>) -> Vec<(i64, i64)> {
+    let min_x = cells.iter().map(|(x, _)| *x).min().unwrap_or(0);
+    let min_y = cells.iter().map(|(_, y)| *y).min().unwrap_or(0);
+    let mut cells: Vec<(i64, i64)> = cells
+        .into_iter()
+        .map(|(x, y)| (x - min_x, y - min_y))
+        .collect();
+
+    cells.sort_unstable();
+
+    return cells;
+}
+
+/// Extended Wechsler encoding of the live cells of a board as they are laid
+/// out, without the prefix or any canonicalization.
+pub fn encode(state: &BoardState) -> String {
+    let cells = (0..state.cells.len())
+        .filter(|i| state.cells[*i] == LIVE)
+        .map(|i| {
+            (
+                (i as i32 % state.width) as i64,
+                (i as i32 / state.width) as i64,
+            )
+        })
+        .collect();
+
+    return encode_cells(&normalize(cells));
+}
+
+fn encode_cells(cells: &[(i64, i64)]) -> String {
+    let width = cells.iter().map(|(x, _)| x + 1).max().unwrap_or(0);
+    let height = cells.iter().map(|(_, y)| y + 1).max().unwrap_or(0);
+    let strips = (height + STRIP_HEIGHT - 1) / STRIP_HEIGHT;
+
+    let mut columns = vec![0u8; (width * strips) as usize];
+
+    for (x, y) in cells {
+        columns[((y / STRIP_HEIGHT) * width + x) as usize] |= 1 << (y % STRIP_HEIGHT);
+    }
+
+    let mut out = String::new();
+
+    for strip in 0..strips {
+        if strip > 0 {
+            out.push('z');
+        }
+
+        let strip = &columns[(strip * width) as usize..((strip + 1) * width) as usize];
+        let len = strip.iter().rposition(|c| *c != 0).map_or(0, |i| i + 1);
+        let mut zeros = 0;
+
+        for column in &strip[..len] {
+            if *column == 0 {
+                zeros += 1;
+                continue;
+            }
+
+            push_zeros(&mut out, zeros);
+            zeros = 0;
+            out.push(char::from(DIGITS[*column as usize]));
+        }
+    }
+
+    return out;
+}
+
+/// Blank columns: "0", "w" for 2, "x" for 3 and "y0" to "yz" for 4 to 39.
+fn push_zeros(out: &mut String, zeros: usize) {
+    let mut zeros = zeros;
+
+    while zeros >= 4 {
+        let run = zeros.min(39);
+
+        out.push('y');
+        out.push(char::from(DIGITS[run - 4]));
+        zeros -= run;
+    }
+
+    out.push_str(match zeros {
+        1 => "0",
+        2 => "w",
+        3 => "x",
+        _ => "",
+    });
+}
+
+/// Board holding a pattern from its apgcode. Only the extended Wechsler part
+/// is used, the prefix is checked but not needed to rebuild the cells.
+pub fn decode(apgcode: &str) -> Result<BoardState, ParseApgcodeError> {
+    let error = |reason: &str| ParseApgcodeError {
+        apgcode: apgcode.to_string(),
+        reason: reason.to_string(),
+    };
+
+    let (prefix, code) = apgcode
+        .split_once('_')
+        .ok_or_else(|| error("expected \"<prefix>_<code>\""))?;
+
+    let valid_prefix = ["xs", "xp", "xq"].iter().any(|p| {
+        prefix
+            .strip_prefix(p)
+            .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+    });
+
+    if !valid_prefix {
+        return Err(error("expected an xs, xp or xq prefix"));
+    }
+
+    let mut cells = Vec::new();
+    let (mut x, mut y) = (0i64, 0i64);
+    let mut chars = code.chars();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '0'..='9' | 'a'..='v' => {
+                let column = DIGITS.iter().position(|d| *d == c as u8).unwrap();
+
+                for bit in 0..STRIP_HEIGHT {
+                    if column & (1 << bit) != 0 {
+                        cells.push((x, y + bit));
+                    }
+                }
+                x += 1;
+            }
+            'w' => x += 2,
+            'x' => x += 3,
+            'y' => {
+                let run = chars
+                    .next()
+                    .and_then(|d| DIGITS.iter().position(|b| *b == d as u8))
+                    .ok_or_else(|| error("expected a digit after 'y'"))?;
+
+                x += run as i64 + 4;
+            }
+            'z' => {
+                x = 0;
+                y += STRIP_HEIGHT;
+            }
+            _ => return Err(error(&format!("unexpected character {:?}", c))),
+        }
+    }
+
+    return state_from_cells(&cells).map_err(|err| error(&err.message));
+}
+
+impl BoardState {
+    pub fn from_apgcode(apgcode: &str) -> Result<BoardState, ParseApgcodeError> {
+        return decode(apgcode);
+    }
+}
+
+#[test]
+fn classify_common_objects() {
+    let classify_rle = |rle: &str| {
+        let state = BoardState::from_rle(rle).unwrap();
+
+        return classify(&state, Rule::conway(), 30);
+    };
+
+    for (rle, apgcode) in [
+        ("x = 2, y = 2\n2o$2o!", "xs4_33"),
+        ("x = 3, y = 4\nbo$obo$obo$bo!", "xs6_696"),
+        ("x = 3, y = 1\n3o!", "xp2_7"),
+        ("x = 3, y = 3\nbo$2bo$3o!", "xq4_153"),
+        ("x = 5, y = 4\nbo2bo$o4b$o3bo$4o!", "xq4_6frc"),
+        ("x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!", "xp15_4r4z4r4"),
+        ("x = 12, y = 2\n2o8b2o$2o8b2o!", "xs8_33y433"),
+    ] {
+        assert_eq!(classify_rle(rle).as_deref(), Some(apgcode));
+
+        let decoded = decode(apgcode).unwrap();
+        assert_eq!(
+            classify(&decoded, Rule::conway(), 30).as_deref(),
+            Some(apgcode)
+        );
+    }
+
+    // R-pentomino takes far longer to settle.
+    assert_eq!(classify_rle("x = 3, y = 3\nb2o$2o$bo!"), None);
+    assert_eq!(classify_rle("x = 1, y = 1\no!"), None);
+
+    assert_eq!(encode(&decode("xs4_33").unwrap()), "33");
+    assert_eq!(encode(&decode("xs2_1yzy01").unwrap()), "1yzy01");
+    assert!(decode("33").is_err());
+    assert!(decode("xz4_33").is_err());
+    assert!(decode("xs4_3!").is_err());
+    assert!(decode("xs4_y").is_err());
+}
@@ -0,0 +1,280 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, BoundingBox, DEAD, LIVE};
+use crate::rule::Rule;
+use crate::statistics::Statistics;
+use crate::topology::Topology;
+
+//
+// Bit-packed board, 64 cells per u64 word. Bit `x % 64` of word `x / 64` in a
+// row holds cell x. Bits past the board width are always zero.
+//
+#[derive(Clone, Debug)]
+pub struct BitBoard {
+    pub width: i32,
+    pub height: i32,
+    pub topology: Topology,
+    words: usize,
+    rows: Vec<u64>,
+}
+
+impl BitBoard {
+    pub fn supports(topology: Topology) -> bool {
+        return matches!(
+            topology,
+            Topology::Bounded
+                | Topology::Torus
+                | Topology::HorizontalCylinder
+                | Topology::VerticalCylinder
+        );
+    }
+
+    pub fn from_state(state: &BoardState) -> BitBoard {
+        assert!(
+            BitBoard::supports(state.topology),
+            "bit-packed boards support bounded, torus and cylinder topologies"
+        );
+
+        let words = (state.width as usize).div_ceil(64);
+        let mut rows = vec![0u64; words * state.height as usize];
+
+        for (i, cell) in state.cells.iter().enumerate() {
+            if *cell == LIVE {
+                let x = i % state.width as usize;
+                let y = i / state.width as usize;
+
+                rows[y * words + x / 64] |= 1 << (x % 64);
+            }
+        }
+
+        return BitBoard {
+            width: state.width,
+            height: state.height,
+            topology: state.topology,
+            words,
+            rows,
+        };
+    }
+
+    pub fn to_state(&self) -> BoardState {
+        let width = self.width as usize;
+        let mut cells = vec![DEAD; width * self.height as usize];
+
+        for (y, row) in cells.chunks_mut(width).enumerate() {
+            let words = &self.rows[y * self.words..(y + 1) * self.words];
+
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = ((words[x / 64] >> (x % 64)) & 1) as u8;
+            }
+        }
+
+        return BoardState {
+            width: self.width,
+            height: self.height,
+            cells,
+            topology: self.topology,
+        };
+    }
+
+    pub fn get(&self, x: i32, y: i32) -> u8 {
+        if x < 0 || y < 0 || x >= self.width || y >= self.height {
+            return DEAD;
+        }
+
+        let word = self.rows[y as usize * self.words + x as usize / 64];
+
+        return ((word >> (x % 64)) & 1) as u8;
+    }
+
+    pub fn population(&self) -> u64 {
+        return self.rows.iter().map(|w| w.count_ones() as u64).sum();
+    }
+
+    fn row(&self, y: i32) -> Option<&[u64]> {
+        let y = if y >= 0 && y < self.height {
+            y
+        } else if self.topology.wraps_y() {
+            y.rem_euclid(self.height)
+        } else {
+            return None;
+        };
+
+        let start = y as usize * self.words;
+
+        return Some(&self.rows[start..start + self.words]);
+    }
+
+    /// Advances one generation of a totalistic two state rule, 64 cells at a
+    /// time. Returns the statistics of the new generation, see
+    /// `Statistics::finish`.
+    pub fn step(&mut self, rule: &Rule) -> Statistics {
+        let words = self.words;
+        let last_bit = (self.width as usize - 1) % 64;
+        let last_mask = if last_bit == 63 {
+            !0
+        } else {
+            (1u64 << (last_bit + 1)) - 1
+        };
+        let wraps_x = self.topology.wraps_x();
+
+        let births: Vec<u8> = (0..=8).filter(|n| rule.births(*n)).collect();
+        let survivals: Vec<u8> = (0..=8).filter(|n| rule.survives(*n)).collect();
+
+        let zeros = vec![0u64; words];
+        let mut next = vec![0u64; self.rows.len()];
+
+        // Row shifted so bit x holds cell x - 1 (west) or x + 1 (east).
+        let shifted = |row: &[u64], i: usize| -> (u64, u64) {
+            let mut west = row[i] << 1;
+            let mut east = row[i] >> 1;
+
+            if i > 0 {
+                west |= row[i - 1] >> 63;
+            } else if wraps_x {
+                west |= (row[words - 1] >> last_bit) & 1;
+            }
+
+            if i + 1 < words {
+                east |= row[i + 1] << 63;
+            } else if wraps_x {
+                east |= (row[0] & 1) << last_bit;
+            }
+
+            return (west, east);
+        };
+
+        for y in 0..self.height {
+            let above = self.row(y - 1).unwrap_or(&zeros);
+            let current = self.row(y).unwrap();
+            let below = self.row(y + 1).unwrap_or(&zeros);
+
+            for i in 0..words {
+                let (aw, ae) = shifted(above, i);
+                let (cw, ce) = shifted(current, i);
+                let (bw, be) = shifted(below, i);
+                let (ac, cc, bc) = (above[i], current[i], below[i]);
+
+                //
+                // Bit-sliced adder: count the eight neighbors of all 64
+                // cells in parallel into a 4 bit number (c3 c2 c1 c0).
+                //
+                let (s_a, k_a) = full_add(aw, ac, ae);
+                let (s_b, k_b) = full_add(cw, ce, bw);
+                let (s_c, k_c) = half_add(bc, be);
+                let (c0, k_d) = full_add(s_a, s_b, s_c);
+                let (t, k_e) = full_add(k_a, k_b, k_c);
+                let (c1, k_f) = half_add(t, k_d);
+                let c2 = k_e ^ k_f;
+                let c3 = k_e & k_f;
+
+                let count = |n: u8| -> u64 {
+                    let bit = |b: u64, i: u8| if n & (1 << i) != 0 { b } else { !b };
+
+                    return bit(c0, 0) & bit(c1, 1) & bit(c2, 2) & bit(c3, 3);
+                };
+
+                let mut born = 0;
+                let mut survive = 0;
+
+                for n in &births {
+                    born |= count(*n);
+                }
+
+                for n in &survivals {
+                    survive |= count(*n);
+                }
+
+                let mut word = (!cc & born) | (cc & survive);
+
+                if i + 1 == words {
+                    word &= last_mask;
+                }
+
+                next[y as usize * words + i] = word;
+            }
+        }
+
+        let previous = std::mem::replace(&mut self.rows, next);
+
+        return self.statistics(&previous);
+    }
+
+    /// Population, births and deaths counted a word at a time against the
+    /// rows of the previous generation.
+    fn statistics(&self, previous: &[u64]) -> Statistics {
+        let mut statistics = Statistics::default();
+        let (mut left, mut right) = (usize::MAX, 0);
+        let (mut top, mut bottom) = (None, 0);
+
+        for (i, (old, new)) in previous.iter().zip(&self.rows).enumerate() {
+            statistics.population += new.count_ones() as u64;
+            statistics.births += (new & !old).count_ones() as u64;
+            statistics.deaths += (old & !new).count_ones() as u64;
+
+            if *new != 0 {
+                let (y, x) = (i / self.words, i % self.words * 64);
+
+                left = left.min(x + new.trailing_zeros() as usize);
+                right = right.max(x + 63 - new.leading_zeros() as usize);
+                top.get_or_insert(y);
+                bottom = y;
+            }
+        }
+
+        statistics.populations[0] = statistics.population;
+
+        if let Some(top) = top {
+            statistics.bounding_box = BoundingBox {
+                x: left as i64,
+                y: top as i64,
+                width: (right - left + 1) as i64,
+                height: (bottom - top + 1) as i64,
+            };
+        }
+
+        return statistics;
+    }
+}
+
+fn half_add(a: u64, b: u64) -> (u64, u64) {
+    return (a ^ b, a & b);
+}
+
+fn full_add(a: u64, b: u64, c: u64) -> (u64, u64) {
+    let t = a ^ b;
+
+    return (t ^ c, (a & b) | (t & c));
+}
+
+#[test]
+fn bitboard_matches_dense() {
+    use crate::life::{Life, Shape};
+
+    for (width, height) in [(1, 1), (5, 7), (64, 10), (70, 33), (130, 3)] {
+        for topology in [
+            Topology::Bounded,
+            Topology::Torus,
+            Topology::HorizontalCylinder,
+        ] {
+            for rule in [Rule::conway(), Rule::parse("B36/S23").unwrap()] {
+                let mut game = Life::new(width, height, Shape::Random, 1, rule);
+                game.set_topology(topology);
+
+                let mut packed = BitBoard::from_state(game.state());
+
+                for _ in 0..10 {
+                    let previous = game.state().clone();
+                    game.tickle();
+                    let mut statistics = packed.step(&rule);
+                    statistics.finish(game.generation(), game.state().cells.len());
+
+                    let mut expected = Statistics::between(&previous, game.state(), &rule);
+                    expected.finish(game.generation(), game.state().cells.len());
+
+                    assert_eq!(packed.to_state().cells, game.state().cells);
+                    assert_eq!(statistics, expected);
+                }
+            }
+        }
+    }
+}
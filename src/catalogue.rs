@@ -0,0 +1,139 @@
+#![allow(dead_code)]
+
+use crate::life::BoardState;
+use crate::rle;
+
+//
+// Classic patterns by name, stored as RLE. Names are lowercase with words
+// separated by '-', lookups also accept spaces, '_' and any case.
+//
+const PATTERNS: &[(&str, &str)] = &[
+    // Still lifes.
+    ("block", "x = 2, y = 2\n2o$2o!"),
+    ("beehive", "x = 4, y = 3\nb2o$o2bo$b2o!"),
+    ("loaf", "x = 4, y = 4\nb2o$o2bo$bobo$2bo!"),
+    ("boat", "x = 3, y = 3\n2o$obo$bo!"),
+    ("ship", "x = 3, y = 3\n2o$obo$b2o!"),
+    ("tub", "x = 3, y = 3\nbo$obo$bo!"),
+    ("pond", "x = 4, y = 4\nb2o$o2bo$o2bo$b2o!"),
+    ("eater-1", "x = 4, y = 4\n2o$obo$2bo$2b2o!"),
+    // Oscillators.
+    ("blinker", "x = 3, y = 1\n3o!"),
+    ("toad", "x = 4, y = 2\nb3o$3o!"),
+    ("beacon", "x = 4, y = 4\n2o$2o$2b2o$2b2o!"),
+    ("clock", "x = 4, y = 4\n2bo$obo$bobo$bo!"),
+    (
+        "pulsar",
+        "x = 13, y = 13\n2b3o3b3o2$o4bobo4bo$o4bobo4bo$o4bobo4bo$2b3o3b3o2$2b3o3b3o$o4bobo4bo$\
+         o4bobo4bo$o4bobo4bo2$2b3o3b3o!",
+    ),
+    ("pentadecathlon", "x = 10, y = 3\n2bo4bo$2ob4ob2o$2bo4bo!"),
+    ("figure-eight", "x = 6, y = 6\n3o$3o$3o$3b3o$3b3o$3b3o!"),
+    (
+        "queen-bee-shuttle",
+        "x = 24, y = 7\n9bo$7bobo$6bobo$2o3bo2bo11b2o$2o4bobo11b2o$7bobo$9bo!",
+    ),
+    // Spaceships.
+    ("glider", "x = 3, y = 3\nbo$2bo$3o!"),
+    ("lwss", "x = 5, y = 4\nbo2bo$o4b$o3bo$4o!"),
+    ("mwss", "x = 6, y = 5\n3bo$bo3bo$o$o4bo$5o!"),
+    ("hwss", "x = 7, y = 5\n3b2o$bo4bo$o$o5bo$6o!"),
+    // Guns.
+    (
+        "gosper-glider-gun",
+        "x = 36, y = 9\n24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$\
+         2o8bo3bob2o4bobo$10bo5bo7bo$11bo3bo$12b2o!",
+    ),
+    // Methuselahs.
+    ("r-pentomino", "x = 3, y = 3\nb2o$2o$bo!"),
+    ("b-heptomino", "x = 4, y = 3\nob2o$3o$bo!"),
+    ("pi-heptomino", "x = 3, y = 3\n3o$obo$obo!"),
+    ("herschel", "x = 3, y = 4\no$3o$obo$2bo!"),
+    ("thunderbird", "x = 3, y = 5\n3o2$bo$bo$bo!"),
+    ("acorn", "x = 7, y = 3\nbo$3bo$2o2b3o!"),
+    ("diehard", "x = 8, y = 3\n6bo$2o$bo3b3o!"),
+];
+
+/// Names of every pattern in the catalogue.
+pub fn names() -> impl Iterator<Item = &'static str> {
+    return PATTERNS.iter().map(|(name, _)| *name);
+}
+
+/// Pattern by name, in a board just big enough to hold it.
+pub fn get(name: &str) -> Option<BoardState> {
+    let name = name.trim().to_ascii_lowercase().replace([' ', '_'], "-");
+    let (_, text) = PATTERNS.iter().find(|(n, _)| *n == name)?;
+
+    // The catalogue is checked by the tests below, entries always parse.
+    return Some(rle::parse(text).unwrap().state);
+}
+
+#[test]
+fn catalogue_patterns_behave() {
+    use crate::apgcode::classify;
+    use crate::life::{Shape, DEAD, LIVE};
+    use crate::rule::Rule;
+
+    for name in names() {
+        assert!(get(name).is_some(), "{} doesn't parse", name);
+    }
+
+    for (name, apgcode) in [
+        ("block", "xs4_33"),
+        ("beehive", "xs6_696"),
+        ("loaf", "xs7_2596"),
+        ("boat", "xs5_253"),
+        ("ship", "xs6_356"),
+        ("tub", "xs4_252"),
+        ("pond", "xs8_6996"),
+        ("blinker", "xp2_7"),
+        ("toad", "xp2_7e"),
+        ("beacon", "xp2_318c"),
+        ("pentadecathlon", "xp15_4r4z4r4"),
+        ("glider", "xq4_153"),
+        ("lwss", "xq4_6frc"),
+        ("mwss", "xq4_27dee6"),
+        ("hwss", "xq4_27deee6"),
+    ] {
+        let state = get(name).unwrap();
+        assert_eq!(
+            classify(&state, Rule::conway(), 30).as_deref(),
+            Some(apgcode),
+            "{}",
+            name
+        );
+    }
+
+    for (name, period) in [
+        ("eater-1", "xs7_"),
+        ("clock", "xp2_"),
+        ("pulsar", "xp3_"),
+        ("figure-eight", "xp8_"),
+        ("queen-bee-shuttle", "xp30_"),
+    ] {
+        let apgcode = classify(&get(name).unwrap(), Rule::conway(), 30).unwrap();
+        assert!(apgcode.starts_with(period), "{} is {}", name, apgcode);
+    }
+
+    assert_eq!(get("Gosper Glider_Gun").unwrap().width, 36);
+    assert!(get("unknown").is_none());
+
+    let state = BoardState::new_named(20, 10, "glider", 17, 4).unwrap();
+    assert_eq!(state.cells.iter().filter(|c| **c == LIVE).count(), 5);
+    assert_eq!(state.get_index(18, 4), LIVE);
+    assert_eq!(state.get_index(19, 6), LIVE);
+
+    let clipped = BoardState::new_named(20, 10, "glider", 18, 0).unwrap();
+    assert_eq!(clipped.cells.iter().filter(|c| **c == LIVE).count(), 3);
+    assert!(BoardState::new_named(20, 10, "unknown", 0, 0).is_none());
+
+    // The blinker is centered whatever the board size.
+    let blinker = BoardState::new(9, 7, Shape::Blinker);
+    assert_eq!((blinker.width, blinker.height), (9, 7));
+    let column: Vec<u8> = (1..6).map(|y| blinker.get_index(4, y)).collect();
+    assert_eq!(column, vec![DEAD, LIVE, LIVE, LIVE, DEAD]);
+    assert_eq!(blinker.cells.iter().filter(|c| **c == LIVE).count(), 3);
+    let small = BoardState::new(5, 5, Shape::Blinker);
+    assert_eq!(small.get_index(2, 1), LIVE);
+    assert_eq!(small.get_index(2, 3), LIVE);
+}
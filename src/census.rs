@@ -0,0 +1,346 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::apgcode;
+use crate::life::{BoardState, BoundingBox, LIVE};
+use crate::pattern::state_from_cells;
+use crate::rule::Rule;
+use crate::sparse::SparseLife;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ObjectKind {
+    StillLife,
+    Oscillator(u64),
+    Spaceship(u64),
+    /// Didn't repeat within the search, e.g. still evolving or a gun.
+    Unknown,
+}
+
+impl fmt::Display for ObjectKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ObjectKind::StillLife => write!(f, "still life"),
+            ObjectKind::Oscillator(period) => write!(f, "oscillator p{}", period),
+            ObjectKind::Spaceship(period) => write!(f, "spaceship p{}", period),
+            ObjectKind::Unknown => write!(f, "unknown"),
+        }
+    }
+}
+
+//
+// Group of cells that evolves on its own.
+//
+#[derive(Clone, Debug)]
+pub struct Object {
+    /// Where the object is on the board.
+    pub bounds: BoundingBox,
+    /// Live cells, in a board the size of `bounds`.
+    pub state: BoardState,
+    pub kind: ObjectKind,
+    pub apgcode: Option<String>,
+}
+
+/// Splits the live cells of a board into objects and classifies each one.
+///
+/// Cells are grouped when they are within twice the range of the rule of
+/// each other at any point of the next `max_period` generations, so objects
+/// that interact (pseudo still lifes, oscillators touching in some phase)
+/// stay together.
+/// The board is run as an unbounded plane, its topology is ignored.
+///
+/// `None` for Generations, colored and B0 rules, whose boards don't split
+/// into objects on a dead background.
+pub fn separate(state: &BoardState, rule: Rule, max_period: u64) -> Option<Vec<Object>> {
+    if rule.states() != 2 || rule.births(0) {
+        return None;
+    }
+
+    let mut game = SparseLife::from_state(state, rule, 0, 0);
+    let start: Vec<(i64, i64)> = game.cells().copied().collect();
+    let mut index: HashMap<(i64, i64), usize> = HashMap::new();
+    let mut parents: Vec<usize> = Vec::new();
+
+    for generation in 0..=max_period {
+        if generation > 0 {
+            game.tick();
+        }
+
+        for cell in game.cells() {
+            let len = index.len();
+            let id = *index.entry(*cell).or_insert(len);
+
+            if id == parents.len() {
+                parents.push(id);
+            }
+        }
+    }
+
+    // Cells ever alive in the window, joined when close enough for their
+    // neighborhoods to overlap: at most twice the rule's range apart in both
+    // axes.
+    let reach = 2 * rule.range() as i64;
+
+    for (&(x, y), &id) in &index {
+        for dy in -reach..=reach {
+            for dx in -reach..=reach {
+                if let Some(other) = index.get(&(x + dx, y + dy)) {
+                    union(&mut parents, id, *other);
+                }
+            }
+        }
+    }
+
+    let mut groups: HashMap<usize, Vec<(i64, i64)>> = HashMap::new();
+
+    for cell in start {
+        let root = find(&mut parents, index[&cell]);
+        groups.entry(root).or_default().push(cell);
+    }
+
+    let mut objects: Vec<Object> = groups
+        .into_values()
+        .map(|cells| classify(&cells, rule, max_period))
+        .collect();
+
+    objects.sort_by_key(|o| (o.bounds.y, o.bounds.x));
+
+    return Some(objects);
+}
+
+fn classify(cells: &[(i64, i64)], rule: Rule, max_period: u64) -> Object {
+    let x = cells.iter().map(|(x, _)| *x).min().unwrap();
+    let y = cells.iter().map(|(_, y)| *y).min().unwrap();
+
+    // Objects come from a board, their bounding box always fits in one.
+    let state = state_from_cells(cells).unwrap();
+    let apgcode = apgcode::classify(&state, rule, max_period);
+
+    let kind = match &apgcode {
+        Some(code) => {
+            let period = code[2..code.find('_').unwrap()].parse().unwrap_or(0);
+
+            match &code[..2] {
+                "xs" => ObjectKind::StillLife,
+                "xp" => ObjectKind::Oscillator(period),
+                _ => ObjectKind::Spaceship(period),
+            }
+        }
+        None => ObjectKind::Unknown,
+    };
+
+    return Object {
+        bounds: BoundingBox {
+            x,
+            y,
+            width: state.width as i64,
+            height: state.height as i64,
+        },
+        state,
+        kind,
+        apgcode,
+    };
+}
+
+fn find(parents: &mut [usize], id: usize) -> usize {
+    let mut root = id;
+
+    while parents[root] != root {
+        root = parents[root];
+    }
+
+    // Path compression.
+    let mut id = id;
+    while parents[id] != root {
+        let next = parents[id];
+        parents[id] = root;
+        id = next;
+    }
+
+    return root;
+}
+
+fn union(parents: &mut [usize], a: usize, b: usize) {
+    let a = find(parents, a);
+    let b = find(parents, b);
+
+    if a != b {
+        parents[a.max(b)] = a.min(b);
+    }
+}
+
+//
+// Object counts of a board, by apgcode.
+//
+#[derive(Clone, Debug)]
+pub struct Census {
+    pub objects: Vec<Object>,
+}
+
+//
+// One line of the census table.
+//
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CensusEntry {
+    pub apgcode: Option<String>,
+    pub kind: ObjectKind,
+    pub count: usize,
+}
+
+impl Census {
+    /// `None` for the rules `separate` can't split into objects.
+    pub fn new(state: &BoardState, rule: Rule, max_period: u64) -> Option<Census> {
+        return Some(Census {
+            objects: separate(state, rule, max_period)?,
+        });
+    }
+
+    /// Objects of each type, most common first.
+    pub fn entries(&self) -> Vec<CensusEntry> {
+        let mut counts: HashMap<(Option<String>, ObjectKind), usize> = HashMap::new();
+
+        for object in &self.objects {
+            *counts
+                .entry((object.apgcode.clone(), object.kind))
+                .or_insert(0) += 1;
+        }
+
+        let mut entries: Vec<CensusEntry> = counts
+            .into_iter()
+            .map(|((apgcode, kind), count)| CensusEntry {
+                apgcode,
+                kind,
+                count,
+            })
+            .collect();
+
+        entries.sort_by(|a, b| {
+            return b
+                .count
+                .cmp(&a.count)
+                .then(a.kind.cmp(&b.kind))
+                .then(a.apgcode.cmp(&b.apgcode));
+        });
+
+        return entries;
+    }
+
+    pub fn population(&self) -> usize {
+        return self
+            .objects
+            .iter()
+            .map(|o| o.state.cells.iter().filter(|c| **c == LIVE).count())
+            .sum();
+    }
+}
+
+impl fmt::Display for Census {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "{:>6}  {:<16}  apgcode", "Count", "Type")?;
+
+        for entry in self.entries() {
+            writeln!(
+                f,
+                "{:>6}  {:<16}  {}",
+                entry.count,
+                entry.kind.to_string(),
+                entry.apgcode.as_deref().unwrap_or("-")
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[test]
+fn census_of_a_settled_board() {
+    use crate::catalogue;
+    use crate::life::Shape;
+
+    let mut board = BoardState::new(60, 40, Shape::Empty);
+
+    for (name, x, y) in [
+        ("block", 2, 2),
+        ("block", 30, 30),
+        ("blinker", 10, 2),
+        ("blinker", 50, 20),
+        ("beehive", 20, 10),
+        ("glider", 40, 2),
+    ] {
+        board.place(&catalogue::get(name).unwrap(), x, y);
+    }
+
+    // Bi-block: two blocks close enough to count as one pseudo still life.
+    let bi_block = BoardState::from_rle("x = 5, y = 2\n2ob2o$2ob2o!").unwrap();
+    board.place(&bi_block, 5, 30);
+
+    let census = Census::new(&board, Rule::conway(), 30).unwrap();
+    let count = |apgcode: &str| {
+        return census
+            .entries()
+            .iter()
+            .find(|e| e.apgcode.as_deref() == Some(apgcode))
+            .map_or(0, |e| e.count);
+    };
+
+    assert_eq!(census.objects.len(), 7);
+    assert_eq!(count("xs4_33"), 2);
+    assert_eq!(count("xp2_7"), 2);
+    assert_eq!(count("xs6_696"), 1);
+    assert_eq!(count("xq4_153"), 1);
+    assert_eq!(count("xs8_rr"), 1);
+    assert_eq!(census.population(), 4 * 2 + 3 * 2 + 6 + 5 + 8);
+    assert_eq!(census.entries()[0].count, 2);
+
+    let glider = census
+        .objects
+        .iter()
+        .find(|o| o.kind == ObjectKind::Spaceship(4))
+        .unwrap();
+    assert_eq!(
+        (glider.bounds.x, glider.bounds.y, glider.bounds.width),
+        (40, 2, 3)
+    );
+
+    let table = census.to_string();
+    assert!(table.contains("     2  still life        xs4_33\n"));
+    assert!(table.contains("     1  spaceship p4      xq4_153\n"));
+
+    // A methuselah is still going after the search window.
+    let r = catalogue::get("r-pentomino").unwrap();
+    let objects = separate(&r, Rule::conway(), 30).unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].kind, ObjectKind::Unknown);
+}
+
+#[test]
+fn census_reach_follows_the_range() {
+    use crate::life::Shape;
+
+    // Every cell survives and none is born, nothing ever moves.
+    let still = Rule::parse("R2,C0,M1,S0..25,B25,NM").unwrap();
+    let objects = |gap: i32| {
+        let mut board = BoardState::new(12, 3, Shape::Empty);
+        board.cells[12 + 1] = LIVE;
+        board.cells[(12 + 1 + gap) as usize] = LIVE;
+
+        return separate(&board, still, 4).unwrap().len();
+    };
+
+    // Range 2 neighborhoods of cells up to 4 apart overlap.
+    assert_eq!(objects(4), 1);
+    assert_eq!(objects(5), 2);
+}
+
+#[test]
+fn census_of_unsupported_rules() {
+    use crate::life::Shape;
+
+    let board = BoardState::new(10, 10, Shape::Glider);
+    let brain = Rule::parse("B2/S/C3").unwrap();
+
+    assert!(Census::new(&board, brain, 10).is_none());
+    assert!(Census::new(&board, Rule::quadlife(), 10).is_none());
+    assert!(Census::new(&board, Rule::parse("B0/S8").unwrap(), 10).is_none());
+}
@@ -0,0 +1,465 @@
+#![allow(dead_code)]
+
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+
+use crate::life::{BoardState, LIVE};
+
+// Generations a board or shape is remembered for, so runs that never settle
+// don't grow the history forever. Longer periods go unnoticed.
+pub const HISTORY_WINDOW: u64 = 4096;
+
+//
+// Board that repeats: generation `start` comes back every `period`
+// generations. Still lifes have a period of 1.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub start: u64,
+    pub period: u64,
+}
+
+//
+// Live cells that come back with the same shape every `period` generations,
+// moved by (dx, dy). Stationary for oscillators and still lifes.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Motion {
+    pub start: u64,
+    pub period: u64,
+    pub dx: i64,
+    pub dy: i64,
+}
+
+impl Motion {
+    pub fn is_moving(&self) -> bool {
+        return self.dx != 0 || self.dy != 0;
+    }
+
+    /// Speed and direction as usually written, "c/4 diagonal", "c/2
+    /// orthogonal", "2c/7 orthogonal" or "(2,1)c/6 oblique".
+    pub fn velocity(&self) -> String {
+        let (dx, dy) = (self.dx.unsigned_abs(), self.dy.unsigned_abs());
+        let period = self.period;
+
+        if dx == 0 && dy == 0 {
+            return "stationary".to_string();
+        }
+
+        if dx != 0 && dy != 0 && dx != dy {
+            let (dx, dy) = (dx.max(dy), dx.min(dy));
+
+            return format!("({},{})c/{} oblique", dx, dy, period);
+        }
+
+        let cells = dx.max(dy);
+        let divisor = gcd(cells, period);
+        let (cells, period) = (cells / divisor, period / divisor);
+        let speed = match (cells, period) {
+            (1, 1) => "c".to_string(),
+            (1, _) => format!("c/{}", period),
+            (_, 1) => format!("{}c", cells),
+            _ => format!("{}c/{}", cells, period),
+        };
+        let direction = if dx == dy { "diagonal" } else { "orthogonal" };
+
+        return format!("{} {}", speed, direction);
+    }
+}
+
+fn gcd(a: u64, b: u64) -> u64 {
+    return if b == 0 { a } else { gcd(b, a % b) };
+}
+
+//
+// Hashes of every board seen so far, by generation, and of the shape of its
+// live cells wherever they are. Only the 64 bit hashes are kept, so a
+// collision could report a cycle that isn't there, which is unlikely enough
+// for runs of any practical length. Only the last `window` generations are
+// kept.
+//
+#[derive(Clone, Debug)]
+pub struct History {
+    window: u64,
+    seen: HashMap<u64, u64>,
+    // Generation and hash of the entries of `seen`, oldest first.
+    seen_order: VecDeque<(u64, u64)>,
+    cycle: Option<Cycle>,
+    // Shape hash to the generation and position it was seen at.
+    shapes: HashMap<u64, (u64, i64, i64)>,
+    shapes_order: VecDeque<(u64, u64)>,
+    motion: Option<Motion>,
+}
+
+impl Default for History {
+    fn default() -> History {
+        return History::with_window(HISTORY_WINDOW);
+    }
+}
+
+impl History {
+    pub fn new() -> History {
+        return History::default();
+    }
+
+    /// History that finds periods of at most `window` generations.
+    pub fn with_window(window: u64) -> History {
+        return History {
+            window,
+            seen: HashMap::new(),
+            seen_order: VecDeque::new(),
+            cycle: None,
+            shapes: HashMap::new(),
+            shapes_order: VecDeque::new(),
+            motion: None,
+        };
+    }
+
+    /// Adds a generation, returns the cycle once the board repeats one seen
+    /// before. Boards stop being recorded once they repeated, shapes once they
+    /// repeated somewhere else.
+    pub fn record(&mut self, generation: u64, state: &BoardState) -> Option<Cycle> {
+        if !self.motion.is_some_and(|m| m.is_moving()) {
+            self.record_shape(generation, state);
+        }
+
+        if self.cycle.is_some() {
+            return self.cycle;
+        }
+
+        let hash = hash_state(state);
+
+        forget(
+            &mut self.seen,
+            &mut self.seen_order,
+            generation,
+            self.window,
+        );
+
+        match self.seen.get(&hash) {
+            Some(start) => {
+                self.cycle = Some(Cycle {
+                    start: *start,
+                    period: generation - start,
+                });
+                self.seen.clear();
+                self.seen_order.clear();
+            }
+            None => {
+                self.seen.insert(hash, generation);
+                self.seen_order.push_back((generation, hash));
+            }
+        }
+
+        return self.cycle;
+    }
+
+    fn record_shape(&mut self, generation: u64, state: &BoardState) {
+        let (hash, x, y) = match shape(state) {
+            Some(shape) => shape,
+            None => return,
+        };
+
+        forget(
+            &mut self.shapes,
+            &mut self.shapes_order,
+            generation,
+            self.window,
+        );
+
+        match self.shapes.get(&hash) {
+            Some((start, x0, y0)) => {
+                let mut dx = x - x0;
+                let mut dy = y - y0;
+
+                // Around a glued edge, the shorter way is the one it went.
+                if state.topology.wraps_x() {
+                    dx = shortest(dx, state.width as i64);
+                }
+
+                if state.topology.wraps_y() {
+                    dy = shortest(dy, state.height as i64);
+                }
+
+                let motion = Motion {
+                    start: *start,
+                    period: generation - start,
+                    dx,
+                    dy,
+                };
+
+                // Standing still so far, the shape may still move later on.
+                if motion.is_moving() {
+                    self.motion = Some(motion);
+                    self.shapes.clear();
+                    self.shapes_order.clear();
+                } else if self.motion.is_none() {
+                    self.motion = Some(motion);
+                }
+            }
+            None => {
+                self.shapes.insert(hash, (generation, x, y));
+                self.shapes_order.push_back((generation, hash));
+            }
+        }
+    }
+
+    pub fn cycle(&self) -> Option<Cycle> {
+        return self.cycle;
+    }
+
+    /// Shape of the live cells repeating, possibly somewhere else. Never
+    /// found for empty boards. A stationary motion is replaced if the shape
+    /// comes back moved later on.
+    ///
+    /// The shape is the one of all live cells of the board: a spaceship next
+    /// to any other object is never found moving, and on a glued board shows
+    /// up as stationary once it went around. See `census` for objects.
+    pub fn motion(&self) -> Option<Motion> {
+        return self.motion;
+    }
+
+    pub fn clear(&mut self) {
+        *self = History::with_window(self.window);
+    }
+}
+
+/// Drops the hashes recorded more than `window` generations before
+/// `generation`.
+fn forget<T>(
+    map: &mut HashMap<u64, T>,
+    order: &mut VecDeque<(u64, u64)>,
+    generation: u64,
+    window: u64,
+) {
+    while let Some((seen, hash)) = order.front() {
+        if generation - seen <= window {
+            break;
+        }
+
+        map.remove(hash);
+        order.pop_front();
+    }
+}
+
+fn shortest(d: i64, size: i64) -> i64 {
+    let d = d.rem_euclid(size);
+
+    return if d > size / 2 { d - size } else { d };
+}
+
+/// Hash of the live cells relative to their bounding box, with the box's
+/// top-left corner. Along a glued edge the box starts after the widest empty
+/// gap, so an object crossing the edge keeps its shape. Twisted edges are
+/// treated like plain glued ones.
+fn shape(state: &BoardState) -> Option<(u64, i64, i64)> {
+    let (width, height) = (state.width as usize, state.height as usize);
+    let mut columns = vec![false; width];
+    let mut rows = vec![false; height];
+
+    for (i, cell) in state.cells.iter().enumerate() {
+        if *cell == LIVE {
+            columns[i % width] = true;
+            rows[i / width] = true;
+        }
+    }
+
+    let x0 = box_start(&columns, state.topology.wraps_x())?;
+    let y0 = box_start(&rows, state.topology.wraps_y())?;
+    let mut cells = Vec::new();
+
+    for (i, cell) in state.cells.iter().enumerate() {
+        if *cell == LIVE {
+            let x = (i % width + width - x0) % width;
+            let y = (i / width + height - y0) % height;
+
+            cells.push((x, y));
+        }
+    }
+
+    cells.sort_unstable();
+
+    let mut hasher = DefaultHasher::new();
+    cells.hash(&mut hasher);
+
+    return Some((hasher.finish(), x0 as i64, y0 as i64));
+}
+
+/// First occupied line, or with wrapping the one after the longest run of
+/// empty lines. `None` when every line is empty.
+fn box_start(occupied: &[bool], wraps: bool) -> Option<usize> {
+    let first = occupied.iter().position(|o| *o)?;
+
+    if !wraps {
+        return Some(first);
+    }
+
+    let len = occupied.len();
+    let (mut best, mut best_start) = (0, first);
+    let mut run = 0;
+
+    // Twice around, so a gap over the edge is counted in one piece.
+    for i in 0..2 * len {
+        if occupied[i % len] {
+            if run > best {
+                best = run;
+                best_start = i % len;
+            }
+            run = 0;
+        } else {
+            run += 1;
+        }
+    }
+
+    return Some(best_start);
+}
+
+pub fn hash_state(state: &BoardState) -> u64 {
+    let mut hasher = DefaultHasher::new();
+
+    state.width.hash(&mut hasher);
+    state.height.hash(&mut hasher);
+    state.cells.hash(&mut hasher);
+
+    return hasher.finish();
+}
+
+#[test]
+fn motion_is_detected() {
+    use crate::life::{Life, Shape};
+    use crate::rule::Rule;
+    use crate::topology::Topology;
+
+    let motion = |state: BoardState, topology: Topology, generations: u64| {
+        let mut game = Life::from_state(state, 1, Rule::conway());
+        game.set_topology(topology);
+        game.track_cycles();
+
+        for _ in 0..generations {
+            game.tick();
+        }
+
+        return game.motion();
+    };
+
+    let glider = Motion {
+        start: 0,
+        period: 4,
+        dx: 1,
+        dy: 1,
+    };
+    let bounded = BoardState::new(20, 20, Shape::Glider);
+    assert_eq!(motion(bounded, Topology::Bounded, 4), Some(glider));
+    assert_eq!(glider.velocity(), "c/4 diagonal");
+
+    // Crossing the glued corner of a torus keeps the shape.
+    let mut crossing = BoardState::new(10, 10, Shape::Empty);
+    crossing.topology = Topology::Torus;
+    crossing.place(&BoardState::new(3, 3, Shape::Glider), 8, 8);
+    assert_eq!(motion(crossing, Topology::Torus, 4), Some(glider));
+
+    let mut lwss = BoardState::new(30, 12, Shape::Empty);
+    lwss.place(&crate::catalogue::get("lwss").unwrap(), 20, 4);
+    let found = motion(lwss, Topology::Torus, 4).unwrap();
+    assert_eq!((found.period, found.dx, found.dy), (4, -2, 0));
+    assert_eq!(found.velocity(), "c/2 orthogonal");
+
+    let blinker = BoardState::new(5, 5, Shape::Blinker);
+    let found = motion(blinker, Topology::Bounded, 2).unwrap();
+    assert!(!found.is_moving());
+    assert_eq!(found.velocity(), "stationary");
+
+    let speed = |period, dx, dy| {
+        return Motion {
+            start: 0,
+            period,
+            dx,
+            dy,
+        }
+        .velocity();
+    };
+    assert_eq!(speed(7, 0, 2), "2c/7 orthogonal");
+    assert_eq!(speed(1, -1, 0), "c orthogonal");
+    assert_eq!(speed(6, 1, -2), "(2,1)c/6 oblique");
+}
+
+#[test]
+fn motion_is_of_the_whole_board() {
+    use crate::life::{Life, Shape};
+    use crate::rule::Rule;
+    use crate::topology::Topology;
+
+    // A glider and a block it never reaches, on opposite diagonals.
+    let mut state = BoardState::new(32, 32, Shape::Empty);
+    state.place(&BoardState::new(3, 3, Shape::Glider), 2, 2);
+    state.place(&crate::catalogue::get("block").unwrap(), 18, 2);
+
+    let mut game = Life::from_state(state, 1, Rule::conway());
+    game.set_topology(Topology::Torus);
+    game.track_cycles();
+
+    for _ in 0..127 {
+        game.tick();
+    }
+    assert_eq!(game.motion(), None);
+
+    // Back where it started after going around: the board only repeats.
+    game.tick();
+    let found = game.motion().unwrap();
+    assert!(!found.is_moving());
+    assert_eq!(found.period, 128);
+    assert_eq!(game.cycle().map(|c| c.period), Some(128));
+
+    // Standing still is not final, the cell moving on is found afterwards.
+    let cell = |x: usize, count: usize| {
+        let mut state = BoardState::new(8, 8, Shape::Empty);
+        state.cells[8 + x..8 + x + count].fill(LIVE);
+        return state;
+    };
+    let mut history = History::new();
+    history.record(0, &cell(1, 1));
+    history.record(1, &cell(1, 2));
+    history.record(2, &cell(1, 1));
+    assert!(!history.motion().unwrap().is_moving());
+    history.record(3, &cell(3, 1));
+    assert_eq!(
+        (
+            history.motion().unwrap().dx,
+            history.motion().unwrap().period
+        ),
+        (2, 3)
+    );
+}
+
+#[test]
+fn history_is_bounded() {
+    use crate::life::{Life, Shape};
+    use crate::rule::Rule;
+    use crate::topology::Topology;
+
+    let mut history = History::with_window(8);
+    let mut board = BoardState::new(20, 20, Shape::Empty);
+
+    // Every generation a new board, nothing ever repeats.
+    for generation in 0..200 {
+        board.cells[generation as usize] = LIVE;
+        assert_eq!(history.record(generation, &board), None);
+        assert!(history.seen.len() <= 9 && history.shapes.len() <= 9);
+    }
+
+    // Periods that fit the window are still found, longer ones are not.
+    let mut game = Life::new(20, 20, Shape::Glider, 1, Rule::conway());
+    game.set_topology(Topology::Torus);
+    let mut short = History::with_window(100);
+    let mut long = History::with_window(79);
+
+    for generation in 1..=200 {
+        game.tick();
+        short.record(generation, game.state());
+        long.record(generation, game.state());
+    }
+
+    assert_eq!(short.cycle().map(|c| c.period), Some(80));
+    assert_eq!(long.cycle(), None);
+}
@@ -0,0 +1,547 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::life::{BoardState, Shape, DEAD, LIVE};
+use crate::rule::Rule;
+
+pub(crate) type NodeId = u32;
+
+pub(crate) const DEAD_LEAF: NodeId = 0;
+pub(crate) const LIVE_LEAF: NodeId = 1;
+
+// Smallest root, 8x8. Keeps the recursion away from the 4x4 base case.
+pub(crate) const MIN_ROOT_LEVEL: u8 = 3;
+
+// Largest supported step is 2^MAX_STEP_LOG2 generations, so the root never
+// grows past what i64 coordinates can address.
+pub const MAX_STEP_LOG2: u8 = 60;
+
+// Once the node store grows past this many nodes it is rebuilt keeping only
+// the nodes reachable from the root.
+const MAX_NODES: usize = 1 << 22;
+
+//
+// Quadtree node of size 2^level x 2^level. Level 0 nodes are single cells.
+//
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Node {
+    pub(crate) level: u8,
+    pub(crate) nw: NodeId,
+    pub(crate) ne: NodeId,
+    pub(crate) sw: NodeId,
+    pub(crate) se: NodeId,
+    pub(crate) population: u64,
+}
+
+//
+// HashLife engine: hash-consed quadtree with memoized results, on an unbounded
+// plane. The root is always centered on (0, 0), covering
+// [-2^(level-1), 2^(level-1)) in both axes.
+//
+#[derive(Debug)]
+pub struct HashLife {
+    pub rule: Rule,
+    nodes: Vec<Node>,
+    table: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+    generation: u64,
+}
+
+impl HashLife {
+    /// Builds the engine from the live cells of a board, with the board's
+    /// top-left corner at (0, 0). The board topology is ignored, HashLife
+    /// always runs on an unbounded plane.
+    pub fn new(state: &BoardState, rule: Rule) -> HashLife {
+        // With B0 the infinite dead background would flip every generation.
+        assert!(!rule.births(0), "HashLife does not support B0 rules");
+        assert!(
+            rule.states() == 2,
+            "HashLife does not support Generations or colored rules"
+        );
+        assert!(
+            rule.range() == 1,
+            "HashLife does not support Larger than Life rules"
+        );
+
+        let mut game = HashLife::empty_engine(rule);
+        let mut level = MIN_ROOT_LEVEL;
+
+        while (1i64 << (level - 1)) < state.width.max(state.height) as i64 {
+            level += 1;
+        }
+
+        let half = 1i64 << (level - 1);
+        game.root = game.build(state, level, -half, -half);
+
+        return game;
+    }
+
+    pub(crate) fn empty_engine(rule: Rule) -> HashLife {
+        let leaf = |population| Node {
+            level: 0,
+            nw: DEAD_LEAF,
+            ne: DEAD_LEAF,
+            sw: DEAD_LEAF,
+            se: DEAD_LEAF,
+            population,
+        };
+
+        return HashLife {
+            rule,
+            nodes: vec![leaf(0), leaf(1)],
+            table: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD_LEAF],
+            root: DEAD_LEAF,
+            generation: 0,
+        };
+    }
+
+    fn build(&mut self, state: &BoardState, level: u8, x: i64, y: i64) -> NodeId {
+        let size = 1i64 << level;
+
+        if x + size <= 0 || y + size <= 0 || x >= state.width as i64 || y >= state.height as i64 {
+            return self.empty(level);
+        }
+
+        if level == 0 {
+            return if state.get_index(x as i32, y as i32) == LIVE {
+                LIVE_LEAF
+            } else {
+                DEAD_LEAF
+            };
+        }
+
+        let half = size / 2;
+        let nw = self.build(state, level - 1, x, y);
+        let ne = self.build(state, level - 1, x + half, y);
+        let sw = self.build(state, level - 1, x, y + half);
+        let se = self.build(state, level - 1, x + half, y + half);
+
+        return self.join(nw, ne, sw, se);
+    }
+
+    pub fn generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    pub fn population(&self) -> u64 {
+        return self.node(self.root).population;
+    }
+
+    pub(crate) fn root(&self) -> NodeId {
+        return self.root;
+    }
+
+    /// Replaces the root with a node built through `join`, e.g. by a pattern
+    /// reader. Small roots are grown to the minimum root size.
+    pub(crate) fn set_root(&mut self, root: NodeId, generation: u64) {
+        assert!(self.node(root).level > 0, "the root can't be a single cell");
+
+        let mut root = root;
+
+        while self.node(root).level < MIN_ROOT_LEVEL {
+            root = self.expand(root);
+        }
+
+        self.root = root;
+        self.generation = generation;
+    }
+
+    pub(crate) fn node(&self, id: NodeId) -> Node {
+        return self.nodes[id as usize];
+    }
+
+    pub(crate) fn join(&mut self, nw: NodeId, ne: NodeId, sw: NodeId, se: NodeId) -> NodeId {
+        let key = [nw, ne, sw, se];
+
+        if let Some(id) = self.table.get(&key) {
+            return *id;
+        }
+
+        let level = self.node(nw).level + 1;
+        let population = [nw, ne, sw, se].iter().fold(0u64, |sum, id| {
+            sum.saturating_add(self.node(*id).population)
+        });
+
+        let id = self.nodes.len() as NodeId;
+        self.nodes.push(Node {
+            level,
+            nw,
+            ne,
+            sw,
+            se,
+            population,
+        });
+        self.table.insert(key, id);
+
+        return id;
+    }
+
+    pub(crate) fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let e = *self.empty.last().unwrap();
+            let id = self.join(e, e, e, e);
+            self.empty.push(id);
+        }
+
+        return self.empty[level as usize];
+    }
+
+    /// Same node one level up, centered in an empty border.
+    fn expand(&mut self, id: NodeId) -> NodeId {
+        let n = self.node(id);
+        let e = self.empty(n.level - 1);
+
+        let nw = self.join(e, e, e, n.nw);
+        let ne = self.join(e, e, n.ne, e);
+        let sw = self.join(e, n.sw, e, e);
+        let se = self.join(n.se, e, e, e);
+
+        return self.join(nw, ne, sw, se);
+    }
+
+    /// Central node one level down.
+    fn centre(&mut self, id: NodeId) -> NodeId {
+        let n = self.node(id);
+        let (nw, ne, sw, se) = (
+            self.node(n.nw),
+            self.node(n.ne),
+            self.node(n.sw),
+            self.node(n.se),
+        );
+
+        return self.join(nw.se, ne.sw, sw.ne, se.nw);
+    }
+
+    /// Node level `level - 1` with the centered result of advancing the
+    /// given node 2^j generations, j <= level - 2.
+    fn successor(&mut self, id: NodeId, j: u8) -> NodeId {
+        let n = self.node(id);
+
+        if n.population == 0 {
+            return self.empty(n.level - 1);
+        }
+
+        let j = j.min(n.level - 2);
+
+        if let Some(result) = self.results.get(&(id, j)) {
+            return *result;
+        }
+
+        let result = if n.level == 2 {
+            self.life_4x4(id)
+        } else {
+            let (nw, ne, sw, se) = (
+                self.node(n.nw),
+                self.node(n.ne),
+                self.node(n.sw),
+                self.node(n.se),
+            );
+
+            //
+            // Nine overlapping sub-squares, one level down.
+            //
+            let n00 = n.nw;
+            let n01 = self.join(nw.ne, ne.nw, nw.se, ne.sw);
+            let n02 = n.ne;
+            let n10 = self.join(nw.sw, nw.se, sw.nw, sw.ne);
+            let n11 = self.join(nw.se, ne.sw, sw.ne, se.nw);
+            let n12 = self.join(ne.sw, ne.se, se.nw, se.ne);
+            let n20 = n.sw;
+            let n21 = self.join(sw.ne, se.nw, sw.se, se.sw);
+            let n22 = n.se;
+
+            let c00 = self.successor(n00, j);
+            let c01 = self.successor(n01, j);
+            let c02 = self.successor(n02, j);
+            let c10 = self.successor(n10, j);
+            let c11 = self.successor(n11, j);
+            let c12 = self.successor(n12, j);
+            let c20 = self.successor(n20, j);
+            let c21 = self.successor(n21, j);
+            let c22 = self.successor(n22, j);
+
+            if j < n.level - 2 {
+                // The sub-squares already moved 2^j, keep their centers.
+                let nw = self.join(c00, c01, c10, c11);
+                let ne = self.join(c01, c02, c11, c12);
+                let sw = self.join(c10, c11, c20, c21);
+                let se = self.join(c11, c12, c21, c22);
+
+                let nw = self.centre(nw);
+                let ne = self.centre(ne);
+                let sw = self.centre(sw);
+                let se = self.centre(se);
+
+                self.join(nw, ne, sw, se)
+            } else {
+                // Half of the way done, advance the other half.
+                let nw = self.join(c00, c01, c10, c11);
+                let ne = self.join(c01, c02, c11, c12);
+                let sw = self.join(c10, c11, c20, c21);
+                let se = self.join(c11, c12, c21, c22);
+
+                let nw = self.successor(nw, j);
+                let ne = self.successor(ne, j);
+                let sw = self.successor(sw, j);
+                let se = self.successor(se, j);
+
+                self.join(nw, ne, sw, se)
+            }
+        };
+
+        self.results.insert((id, j), result);
+
+        return result;
+    }
+
+    /// Base case: central 2x2 of a 4x4 node after one generation.
+    fn life_4x4(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[DEAD; 4]; 4];
+
+        for (y, row) in cells.iter_mut().enumerate() {
+            for (x, cell) in row.iter_mut().enumerate() {
+                *cell = self.get_in(id, x as i64, y as i64);
+            }
+        }
+
+        let mut next = [DEAD_LEAF; 4];
+
+        for (i, (x, y)) in [(1, 1), (2, 1), (1, 2), (2, 2)].iter().enumerate() {
+            let mut neighborhood = 0;
+
+            for ((dx, dy), weight) in self.rule.neighbors() {
+                if cells[(*y as i32 + dy) as usize][(*x as i32 + dx) as usize] == LIVE {
+                    neighborhood += weight;
+                }
+            }
+
+            if self.rule.next(cells[*y][*x], neighborhood) == LIVE {
+                next[i] = LIVE_LEAF;
+            }
+        }
+
+        return self.join(next[0], next[1], next[2], next[3]);
+    }
+
+    /// Cell at (x, y) relative to the top-left corner of a node.
+    pub(crate) fn get_in(&self, id: NodeId, x: i64, y: i64) -> u8 {
+        let mut id = id;
+        let (mut x, mut y) = (x, y);
+
+        loop {
+            let n = self.node(id);
+
+            if n.population == 0 {
+                return DEAD;
+            }
+
+            if n.level == 0 {
+                return LIVE;
+            }
+
+            let half = 1i64 << (n.level - 1);
+
+            id = match (x >= half, y >= half) {
+                (false, false) => n.nw,
+                (true, false) => n.ne,
+                (false, true) => n.sw,
+                (true, true) => n.se,
+            };
+            x %= half;
+            y %= half;
+        }
+    }
+
+    /// Cell at (x, y) on the plane.
+    pub fn get(&self, x: i64, y: i64) -> u8 {
+        let level = self.node(self.root).level;
+        let half = 1i64 << (level - 1);
+
+        if x < -half || y < -half || x >= half || y >= half {
+            return DEAD;
+        }
+
+        return self.get_in(self.root, x + half, y + half);
+    }
+
+    /// Root population is entirely inside the central quarter, so advancing
+    /// it can't push cells out of the successor.
+    fn is_padded(&mut self) -> bool {
+        let centre = self.centre(self.root);
+        let inner = self.centre(centre);
+
+        return self.node(inner).population == self.population();
+    }
+
+    /// Advance 2^k generations.
+    pub fn step_pow2(&mut self, k: u8) {
+        assert!(k <= MAX_STEP_LOG2);
+
+        if self.nodes.len() > MAX_NODES {
+            self.collect_garbage();
+        }
+
+        while self.node(self.root).level < k + 3 || !self.is_padded() {
+            self.root = self.expand(self.root);
+        }
+
+        self.root = self.successor(self.root, k);
+        self.generation += 1 << k;
+    }
+
+    /// Advance any number of generations, in power of two jumps.
+    pub fn step(&mut self, generations: u64) {
+        for k in 0..64 {
+            if generations & (1 << k) != 0 {
+                self.step_pow2(k);
+            }
+        }
+    }
+
+    pub fn tick(&mut self) {
+        self.step(1);
+    }
+
+    /// Copies the window of the plane with top-left corner (x, y) into a
+    /// board.
+    pub fn to_board_state(&self, x: i64, y: i64, width: i32, height: i32) -> BoardState {
+        let mut state = BoardState::new(width, height, Shape::Empty);
+        let level = self.node(self.root).level;
+        let half = 1i64 << (level - 1);
+
+        self.fill(&mut state, self.root, -half - x, -half - y);
+
+        return state;
+    }
+
+    /// Writes the live cells of a node at (x, y) board coordinates.
+    fn fill(&self, state: &mut BoardState, id: NodeId, x: i64, y: i64) {
+        let n = self.node(id);
+        let size = 1i64 << n.level;
+
+        if n.population == 0
+            || x + size <= 0
+            || y + size <= 0
+            || x >= state.width as i64
+            || y >= state.height as i64
+        {
+            return;
+        }
+
+        if n.level == 0 {
+            state.cells[(y * state.width as i64 + x) as usize] = LIVE;
+            return;
+        }
+
+        let half = size / 2;
+
+        self.fill(state, n.nw, x, y);
+        self.fill(state, n.ne, x + half, y);
+        self.fill(state, n.sw, x, y + half);
+        self.fill(state, n.se, x + half, y + half);
+    }
+
+    /// Rebuilds the node store keeping only what the root references, and
+    /// drops the memoized results.
+    pub fn collect_garbage(&mut self) {
+        let mut fresh = HashLife::empty_engine(self.rule);
+        let mut copied = HashMap::new();
+
+        fresh.root = fresh.copy_from(self, self.root, &mut copied);
+        fresh.generation = self.generation;
+
+        *self = fresh;
+    }
+
+    fn copy_from(
+        &mut self,
+        other: &HashLife,
+        id: NodeId,
+        copied: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        let n = other.node(id);
+
+        if n.level == 0 {
+            return id;
+        }
+
+        if n.population == 0 {
+            return self.empty(n.level);
+        }
+
+        if let Some(new_id) = copied.get(&id) {
+            return *new_id;
+        }
+
+        let nw = self.copy_from(other, n.nw, copied);
+        let ne = self.copy_from(other, n.ne, copied);
+        let sw = self.copy_from(other, n.sw, copied);
+        let se = self.copy_from(other, n.se, copied);
+        let new_id = self.join(nw, ne, sw, se);
+
+        copied.insert(id, new_id);
+
+        return new_id;
+    }
+}
+
+#[test]
+fn hashlife_matches_life() {
+    use crate::life::Life;
+    use crate::sparse::SparseLife;
+
+    // Non-totalistic rules tell neighbors apart, so every engine has to
+    // number them the same way.
+    for rule in [Rule::conway(), Rule::parse("B3/S2-i34q").unwrap()] {
+        // Soup in the middle of a board big enough that the edges don't matter.
+        let mut game = Life::new(64, 64, Shape::Empty, 1, rule);
+        let seed = BoardState::new(16, 16, Shape::Random);
+
+        for y in 0..16 {
+            for x in 0..16 {
+                let i = ((y + 24) * 64 + x + 24) as usize;
+                game.state_mut().cells[i] = seed.cells[(y * 16 + x) as usize];
+            }
+        }
+
+        let mut hashlife = HashLife::new(game.state(), rule);
+        let mut sparse = SparseLife::from_state(game.state(), rule, 0, 0);
+
+        for _ in 0..20 {
+            game.tickle();
+        }
+        hashlife.step(20);
+        sparse.step(20);
+
+        assert_eq!(hashlife.generation(), 20);
+        assert_eq!(
+            hashlife.to_board_state(0, 0, 64, 64).cells,
+            game.state().cells
+        );
+        assert_eq!(
+            sparse.to_board_state(0, 0, 64, 64).cells,
+            game.state().cells
+        );
+    }
+}
+
+#[test]
+fn hashlife_moves_glider_far() {
+    let glider = BoardState::new(3, 3, Shape::Glider);
+    let mut hashlife = HashLife::new(&glider, Rule::conway());
+
+    // A glider moves one cell diagonally every 4 generations.
+    hashlife.step_pow2(40);
+
+    let offset = 1i64 << 38;
+    assert_eq!(hashlife.population(), 5);
+    assert_eq!(
+        hashlife.to_board_state(offset, offset, 3, 3).cells,
+        glider.cells
+    );
+}
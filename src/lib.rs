@@ -1,10 +1,34 @@
+mod apgcode;
+mod bitboard;
+mod catalogue;
+mod census;
+mod cycle;
+mod hashlife;
 mod life;
+mod life105;
+mod life106;
 mod life_image;
+mod macrocell;
+mod pattern;
+mod plaintext;
+mod recorder;
+mod rle;
+mod rule;
+mod ruletable;
+mod soup;
+mod sparse;
+mod statistics;
+mod topology;
+mod transform;
 
 use libc;
 use life::{Life, Shape, DEAD_CHAR, LIVE, LIVE_CHAR};
 use life_image::draw_image_data_url;
-use std::ffi::CString;
+use recorder::{Format, RecordOptions, Recorder};
+use rule::Rule;
+use soup::{Soup, Symmetry};
+use statistics::Statistics;
+use std::ffi::{CStr, CString};
 // use std::ffi::c_void;
 
 #[no_mangle] // *mut libc::c_void
@@ -14,8 +38,14 @@ pub extern "C" fn init_state_empty(width: i32, height: i32, n_workers: i32) -> *
     assert!(n_workers > 0);
 
     // println!("{:?}, {:?}", width, height);
-    let game = Life::new(width, height, Shape::Empty, n_workers as usize);
-    // println!("{:?}, {:?}", game.state.width, game.state.height);
+    let game = Life::new(
+        width,
+        height,
+        Shape::Empty,
+        n_workers as usize,
+        Rule::default(),
+    );
+    // println!("{:?}, {:?}", game.state().width, game.state().height);
     // for i in 0..(game.height*game.width) {
     //     game.cells[i as usize] = random::<bool>() as u8;
     // }
@@ -26,23 +56,55 @@ pub extern "C" fn init_state_empty(width: i32, height: i32, n_workers: i32) -> *
     // return Box::into_raw(Box::new(game)) as *mut libc::c_void;
 }
 
+/// 50% soup from a fresh seed, see `get_seed` to replay it with
+/// `init_state_random_seeded`.
 #[no_mangle] // *mut libc::c_void
 pub extern "C" fn init_state_random(width: i32, height: i32, n_workers: i32) -> *mut Life {
     assert!(width > 0);
     assert!(height > 0);
     assert!(n_workers > 0);
 
-    // println!("{:?}, {:?}", width, height);
-    let game = Life::new(width, height, Shape::Random, n_workers as usize);
-    // println!("{:?}, {:?}", game.state.width, game.state.height);
-    // for i in 0..(game.height*game.width) {
-    //     game.cells[i as usize] = random::<bool>() as u8;
-    // }
+    let game = Life::new(
+        width,
+        height,
+        Shape::Random,
+        n_workers as usize,
+        Rule::default(),
+    );
 
-    let raw = Box::into_raw(Box::new(game));
-    // println!("return ptr {:?}", raw);
-    return raw;
-    // return Box::into_raw(Box::new(game)) as *mut libc::c_void;
+    return Box::into_raw(Box::new(game));
+}
+
+/// Reproducible `init_state_random`. `symmetry` indexes `Symmetry::ALL`:
+/// 0 C1, 1 C2, 2 C4, 3 D2, 4 D4, 5 D8.
+#[no_mangle] // *mut libc::c_void
+pub extern "C" fn init_state_random_seeded(
+    width: i32,
+    height: i32,
+    n_workers: i32,
+    seed: u64,
+    density: f64,
+    symmetry: i32,
+) -> *mut Life {
+    assert!(width > 0);
+    assert!(height > 0);
+    assert!(n_workers > 0);
+    assert!(symmetry >= 0 && (symmetry as usize) < Symmetry::ALL.len());
+
+    let soup = Soup {
+        density,
+        symmetry: Symmetry::ALL[symmetry as usize],
+        ..Soup::new(seed)
+    };
+    let game = Life::new(
+        width,
+        height,
+        Shape::Soup(soup),
+        n_workers as usize,
+        Rule::default(),
+    );
+
+    return Box::into_raw(Box::new(game));
 }
 
 #[no_mangle] // *mut libc::c_void
@@ -52,8 +114,14 @@ pub extern "C" fn init_state_glider(width: i32, height: i32, n_workers: i32) ->
     assert!(n_workers > 0);
 
     // println!("{:?}, {:?}", width, height);
-    let game = Life::new(width, height, Shape::Glider, n_workers as usize);
-    // println!("{:?}, {:?}", game.state.width, game.state.height);
+    let game = Life::new(
+        width,
+        height,
+        Shape::Glider,
+        n_workers as usize,
+        Rule::default(),
+    );
+    // println!("{:?}, {:?}", game.state().width, game.state().height);
     // for i in 0..(game.height*game.width) {
     //     game.cells[i as usize] = random::<bool>() as u8;
     // }
@@ -72,18 +140,18 @@ pub extern "C" fn next_state(game_ptr: *mut libc::c_void) -> *mut libc::c_char {
     let mut game = unsafe { Box::from_raw(game_ptr as *mut Life) };
 
     let mut buff = String::from("");
-    // println!("{:?}", (game.state.width, game.state.height));
+    // println!("{:?}", (game.state().width, game.state().height));
     game.tick();
     // game.tickle();
 
-    for (i, cell) in game.state.cells.iter().enumerate() {
+    for (i, cell) in game.state().cells.iter().enumerate() {
         if *cell == LIVE {
             buff.push_str(LIVE_CHAR);
         } else {
             buff.push_str(DEAD_CHAR);
         }
 
-        if (i as i32 + 1) % game.state.width == 0 {
+        if (i as i32 + 1) % game.state().width == 0 {
             buff.push_str("\n");
         }
     }
@@ -102,10 +170,10 @@ pub extern "C" fn next_state_img(game_ptr: *mut libc::c_void) -> *mut libc::c_ch
     // let mut game = unsafe { Box::from_raw(game_ptr as *mut Life) };
     let mut game = unsafe { Box::from_raw(game_ptr as *mut Life) };
 
-    // println!("{:?}", (game.state.width, game.state.height));
+    // println!("{:?}", (game.state().width, game.state().height));
     game.tickle();
 
-    let data = draw_image_data_url(&game.state);
+    let data = draw_image_data_url(game.state(), &game.rule);
 
     let _raw = Box::into_raw(Box::new(game));
     // println!("return ptr {:?}", raw);
@@ -114,6 +182,64 @@ pub extern "C" fn next_state_img(game_ptr: *mut libc::c_void) -> *mut libc::c_ch
     return c_str_song.into_raw();
 }
 
+#[no_mangle]
+pub extern "C" fn get_statistics(game_ptr: *mut libc::c_void) -> Statistics {
+    let game = unsafe { Box::from_raw(game_ptr as *mut Life) };
+
+    let statistics = game.statistics();
+
+    let _raw = Box::into_raw(game);
+
+    return statistics;
+}
+
+/// Seed of the soup the game was filled from, 0 for boards that are not
+/// soups.
+#[no_mangle]
+pub extern "C" fn get_seed(game_ptr: *mut libc::c_void) -> u64 {
+    let game = unsafe { Box::from_raw(game_ptr as *mut Life) };
+
+    let seed = game.seed().unwrap_or(0);
+
+    let _raw = Box::into_raw(game);
+
+    return seed;
+}
+
+/// Runs the game for `max_generations`, writing every `interval`th
+/// generation's statistics to `path`, as JSON lines for `.json`, `.jsonl`
+/// and `.ndjson` files and CSV otherwise. Returns 0, or -1 if the file
+/// couldn't be written.
+#[no_mangle]
+pub extern "C" fn record_statistics(
+    game_ptr: *mut libc::c_void,
+    path: *const libc::c_char,
+    interval: u64,
+    max_generations: u64,
+) -> i32 {
+    assert!(!path.is_null());
+    assert!(interval > 0);
+
+    let mut game = unsafe { Box::from_raw(game_ptr as *mut Life) };
+    let path = unsafe { CStr::from_ptr(path) }
+        .to_string_lossy()
+        .into_owned();
+
+    let mut recorder = Recorder::new(RecordOptions {
+        format: Format::from_path(&path).unwrap_or(Format::Csv),
+        interval,
+        max_generations,
+    });
+    recorder.run(&mut game);
+
+    let _raw = Box::into_raw(game);
+
+    return match recorder.save(&path) {
+        Ok(()) => 0,
+        Err(_) => -1,
+    };
+}
+
 #[no_mangle]
 pub extern "C" fn free_char_p(s: *mut libc::c_char) {
     _ = unsafe {
@@ -1,30 +1,73 @@
 #![allow(dead_code)]
 
 use rand::random;
-use std::sync::{Arc, Mutex};
+use std::cell::OnceCell;
+use std::sync::Arc;
 use std::thread;
 use std::time;
-use threadpool::ThreadPool;
+
+use crate::bitboard::BitBoard;
+use crate::catalogue;
+use crate::census::Census;
+use crate::cycle::{Cycle, History, Motion};
+use crate::rule::{Neighborhood, Rule};
+use crate::soup::Soup;
+use crate::statistics::Statistics;
+use crate::topology::{ParseTopologyError, Topology};
+use crate::transform::{Blend, Transform};
 
 pub const LIVE: u8 = 1;
 pub const DEAD: u8 = 0;
 
+// Side of the square tiles used to skip regions that didn't change.
+pub const TILE_SIZE: i32 = 32;
+
+// Longest period looked for when classifying the objects of a settled board.
+pub const CENSUS_MAX_PERIOD: u64 = 60;
+
 pub const LIVE_CHAR: &str = "\u{2588}\u{2588}"; // \u2588 OR \u2588
 pub const DEAD_CHAR: &str = "\u{2591}\u{2591}";
+pub const DYING_CHAR: &str = "\u{2592}\u{2592}";
 
 pub enum Shape {
     Empty,
     Random,
     Glider,
     Blinker,
+    /// Seeded random fill, see `Soup`.
+    Soup(Soup),
 }
 
+impl Shape {
+    /// `Random` as a soup with a fresh seed, which can be logged to replay
+    /// the run. Other shapes are kept.
+    pub fn seeded(self) -> Shape {
+        return match self {
+            Shape::Random => Shape::Soup(Soup::new(random())),
+            shape => shape,
+        };
+    }
+}
+
+//
+// Rectangle of cells, e.g. the extent of the live cells of a pattern.
+//
 #[repr(C)]
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BoundingBox {
+    pub x: i64,
+    pub y: i64,
+    pub width: i64,
+    pub height: i64,
+}
+
+#[repr(C)]
+#[derive(Clone, Debug)]
 pub struct BoardState {
     pub width: i32,
     pub height: i32,
     pub cells: Vec<u8>,
+    pub topology: Topology,
 }
 
 impl BoardState {
@@ -32,8 +75,9 @@ impl BoardState {
         return match init {
             Shape::Empty => BoardState::new_empty(width, height),
             Shape::Random => BoardState::new_random(width, height),
+            Shape::Soup(soup) => BoardState::new_soup(width, height, &soup),
             Shape::Glider => BoardState::new_glider(width, height),
-            Shape::Blinker => BoardState::new_blinker(),
+            Shape::Blinker => BoardState::new_blinker(width, height),
         };
     }
 
@@ -43,21 +87,25 @@ impl BoardState {
             width,
             height,
             cells,
+            topology: Topology::Bounded,
         };
     }
 
-    fn new_random(width: i32, height: i32) -> BoardState {
-        let mut cells = vec![DEAD; (width * height) as usize];
+    /// Empty board sized and glued as described by a Golly bounded grid spec,
+    /// e.g. "T80,38" or "K80*,38".
+    pub fn from_golly_grid(spec: &str) -> Result<BoardState, ParseTopologyError> {
+        let (width, height, topology) = Topology::parse_golly(spec)?;
+        let mut state = BoardState::new_empty(width, height);
 
-        for i in 0..(height * width) {
-            cells[i as usize] = random::<bool>() as u8;
-        }
+        state.topology = topology;
 
-        return BoardState {
-            width,
-            height,
-            cells,
-        };
+        return Ok(state);
+    }
+
+    /// 50% soup with a fresh seed, use `Shape::Soup` to be able to replay
+    /// it.
+    fn new_random(width: i32, height: i32) -> BoardState {
+        return BoardState::new_soup(width, height, &Soup::new(random()));
     }
 
     fn new_glider(width: i32, height: i32) -> BoardState {
@@ -73,23 +121,34 @@ impl BoardState {
             width,
             height,
             cells,
+            topology: Topology::Bounded,
         };
     }
 
-    fn new_blinker() -> BoardState {
-        let cells = Vec::from([
-            0, 0, 0, 0, 0, //
-            0, 0, 1, 0, 0, //
-            0, 0, 1, 0, 0, //
-            0, 0, 1, 0, 0, //
-            0, 0, 0, 0, 0, //
-        ]);
-
-        return BoardState {
-            width: 5,
-            height: 5,
-            cells,
+    fn new_blinker(width: i32, height: i32) -> BoardState {
+        let mut state = BoardState::new_empty(width, height);
+        let blinker = BoardState {
+            width: 1,
+            height: 3,
+            cells: vec![LIVE; 3],
+            topology: Topology::Bounded,
         };
+
+        // Vertical, in the middle of the board.
+        state.place(&blinker, width / 2, height / 2 - 1);
+
+        return state;
+    }
+
+    /// Empty board with a pattern from the catalogue, its top-left corner at
+    /// (x, y). `None` if the catalogue has no pattern by that name.
+    pub fn new_named(width: i32, height: i32, name: &str, x: i32, y: i32) -> Option<BoardState> {
+        let pattern = catalogue::get(name)?;
+        let mut state = BoardState::new_empty(width, height);
+
+        state.place(&pattern, x, y);
+
+        return Some(state);
     }
 
     pub fn get_index(&self, x: i32, y: i32) -> u8 {
@@ -100,30 +159,155 @@ impl BoardState {
         return DEAD;
     }
 
-    fn get_neighbors(&self, x: i32, y: i32) -> u8 {
-        return self.get_index(x - 1, y - 1)
-            + self.get_index(x, y - 1)
-            + self.get_index(x + 1, y - 1)
-            + self.get_index(x + 1, y)
-            + self.get_index(x + 1, y + 1)
-            + self.get_index(x, y + 1)
-            + self.get_index(x - 1, y + 1)
-            + self.get_index(x - 1, y);
+    /// Copies a pattern onto the board with its top-left corner at (x, y).
+    /// Cells past a glued edge wrap around, past a bounded edge are dropped.
+    /// See `stamp` for rotations, reflections and blend modes.
+    pub fn place(&mut self, pattern: &BoardState, x: i32, y: i32) {
+        self.stamp(pattern, x, y, Transform::Identity, Blend::Overwrite);
+    }
+
+    /// Number of tiles across and down, see `TILE_SIZE`.
+    pub fn tiles_xy(&self) -> (i32, i32) {
+        return (
+            (self.width + TILE_SIZE - 1) / TILE_SIZE,
+            (self.height + TILE_SIZE - 1) / TILE_SIZE,
+        );
+    }
+
+    pub fn tiles(&self) -> usize {
+        let (tiles_x, tiles_y) = self.tiles_xy();
+
+        return (tiles_x * tiles_y) as usize;
+    }
+
+    /// Like `get_index` but coordinates off the board are resolved through
+    /// the board topology.
+    pub fn get_wrapped(&self, x: i32, y: i32) -> u8 {
+        if self.topology == Topology::Bounded {
+            return self.get_index(x, y);
+        }
+
+        return match self.topology.map(x, y, self.width, self.height) {
+            Some((x, y)) => self.cells[(y * self.width + x) as usize],
+            None => DEAD,
+        };
+    }
+
+    /// Sum of the weights of the live neighbors, see `Rule::neighbors`.
+    /// Dying cells of Generations rules don't count.
+    fn get_neighborhood(&self, x: i32, y: i32, neighbors: &[((i32, i32), u32)]) -> u32 {
+        let mut neighborhood = 0;
+
+        for ((dx, dy), weight) in neighbors {
+            if self.get_wrapped(x + dx, y + dy) == LIVE {
+                neighborhood += weight;
+            }
+        }
+
+        return neighborhood;
+    }
+
+    /// Like `get_neighborhood` for colored rules, with the number of live
+    /// neighbors of each color.
+    fn get_colored_neighborhood(
+        &self,
+        x: i32,
+        y: i32,
+        neighbors: &[((i32, i32), u32)],
+        rule: &Rule,
+    ) -> (u32, [u32; 4]) {
+        let mut neighborhood = 0;
+        let mut parents = [0; 4];
+
+        for ((dx, dy), weight) in neighbors {
+            let cell = self.get_wrapped(x + dx, y + dy);
+
+            if rule.is_alive(cell) {
+                neighborhood += weight;
+                parents[cell as usize - 1] += 1;
+            }
+        }
+
+        return (neighborhood, parents);
     }
 }
 
+//
+// Storage used to compute the next generation.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Backend {
+    /// One byte per cell, `state` is stepped directly.
+    Dense,
+    /// 64 cells per word, `state` is only unpacked when it's read.
+    BitPacked,
+}
+
 #[repr(C)]
 #[derive(Debug)]
 pub struct Life {
-    pub state: Arc<BoardState>,
-    pool: ThreadPool,
+    // Board of the current generation, see `state`. Left empty by the
+    // bit-packed backend until someone reads it.
+    state: OnceCell<BoardState>,
+    pub rule: Rule,
     chunk_intervals: Vec<(usize, usize)>,
+    packed: Option<BitBoard>,
+    // The board was changed from outside, through `state_mut` or
+    // `set_state`, since the last generation.
+    stale: bool,
+    // Tiles that may change in the next generation, see `TILE_SIZE`.
+    dirty: Arc<Vec<bool>>,
+    active_tiles: usize,
+    generation: u64,
+    // Board hashes for cycle detection, `None` until `track_cycles`.
+    history: Option<History>,
+    statistics: Statistics,
+    // Seed of the soup the board was filled from, see `seed`.
+    seed: Option<u64>,
 }
 
 impl Life {
-    pub fn new(width: i32, height: i32, init: Shape, n_workers: usize) -> Life {
-        let state = Arc::new(BoardState::new(width, height, init));
-        let pool = ThreadPool::new(n_workers);
+    pub fn new(width: i32, height: i32, init: Shape, n_workers: usize, rule: Rule) -> Life {
+        return Life::new_with_backend(width, height, init, n_workers, rule, Backend::Dense);
+    }
+
+    pub fn new_with_backend(
+        width: i32,
+        height: i32,
+        init: Shape,
+        n_workers: usize,
+        rule: Rule,
+        backend: Backend,
+    ) -> Life {
+        let init = init.seeded();
+        let seed = match &init {
+            Shape::Soup(soup) => Some(soup.seed),
+            _ => None,
+        };
+
+        let mut game = Life::from_state_with_backend(
+            BoardState::new(width, height, init),
+            n_workers,
+            rule,
+            backend,
+        );
+        game.seed = seed;
+
+        return game;
+    }
+
+    /// Game seeded from an existing board, e.g. one read from a pattern file.
+    pub fn from_state(state: BoardState, n_workers: usize, rule: Rule) -> Life {
+        return Life::from_state_with_backend(state, n_workers, rule, Backend::Dense);
+    }
+
+    pub fn from_state_with_backend(
+        state: BoardState,
+        n_workers: usize,
+        rule: Rule,
+        backend: Backend,
+    ) -> Life {
+        let (width, height) = (state.width, state.height);
         let mut chunk_intervals = Vec::new();
 
         //
@@ -138,107 +322,370 @@ impl Life {
 
         chunk_intervals[n_workers - 1].1 += len % n_workers;
 
+        let packed = match backend {
+            Backend::Dense => None,
+            Backend::BitPacked => {
+                assert!(
+                    rule.states() == 2,
+                    "bit-packed boards do not support Generations or colored rules"
+                );
+                assert!(
+                    rule.is_totalistic(),
+                    "bit-packed boards do not support non-totalistic rules"
+                );
+                assert!(
+                    rule.neighborhood() == Neighborhood::Moore,
+                    "bit-packed boards only support the Moore neighborhood"
+                );
+                Some(BitBoard::from_state(&state))
+            }
+        };
+
+        let tiles = state.tiles();
+        let statistics = Statistics::of(&state, &rule, 0);
+
         return Life {
-            state,
-            pool,
+            state: OnceCell::from(state),
+            stale: false,
+            rule,
             chunk_intervals,
+            packed,
+            dirty: Arc::new(vec![true; tiles]),
+            active_tiles: tiles,
+            generation: 0,
+            history: None,
+            statistics,
+            seed: None,
         };
     }
 
-    pub fn tick(&mut self) {
-        let next_cells = vec![DEAD; (self.state.width * self.state.height) as usize];
-        let c_lock = Arc::new(Mutex::new(next_cells));
-
-        for interval in &self.chunk_intervals {
-            let state = Arc::clone(&self.state);
-            let c_lock2 = c_lock.clone();
-
-            let from = interval.0 as i32;
-            let to = interval.1 as i32;
-
-            self.pool.execute(move || {
-                for i in from..to {
-                    let x = i % state.width;
-                    let y = i / state.width;
-
-                    let neighbors = state.get_neighbors(x, y);
-
-                    if state.cells[i as usize] == LIVE {
-                        if neighbors <= 1 {
-                            // c_lock2.lock().unwrap()[i as usize] = DEAD;
-                        } else if neighbors >= 4 {
-                            // c_lock2.lock().unwrap()[i as usize] = DEAD;
-                        } else {
-                            c_lock2.lock().unwrap()[i as usize] = LIVE;
-                        }
-                    } else {
-                        if neighbors == 3 {
-                            c_lock2.lock().unwrap()[i as usize] = LIVE;
-                        } else {
-                            // c_lock2.lock().unwrap()[i as usize] = DEAD;
-                        }
-                    }
-                }
-            });
+    pub fn backend(&self) -> Backend {
+        return match self.packed {
+            Some(_) => Backend::BitPacked,
+            None => Backend::Dense,
+        };
+    }
+
+    pub fn set_topology(&mut self, topology: Topology) {
+        if self.packed.is_some() {
+            assert!(
+                BitBoard::supports(topology),
+                "bit-packed boards support bounded, torus and cylinder topologies"
+            );
         }
 
-        self.pool.join();
+        self.state_mut().topology = topology;
+    }
 
-        let cells = c_lock.lock().unwrap();
-        let width = self.state.width;
-        let height = self.state.height;
-        let next_state = BoardState {
-            width,
-            height,
-            cells: cells.to_vec(),
+    /// Number of tiles recomputed in the last generation.
+    pub fn active_tiles(&self) -> usize {
+        return self.active_tiles;
+    }
+
+    pub fn tiles(&self) -> usize {
+        return self.state().tiles();
+    }
+
+    /// Generations computed since the game was created.
+    pub fn generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    /// Starts hashing every generation so `cycle` can tell when the board
+    /// repeats. Costs a hash of the board and 16 bytes per generation, for
+    /// the last `HISTORY_WINDOW` generations.
+    pub fn track_cycles(&mut self) {
+        let mut history = History::new();
+
+        history.record(self.generation, self.state());
+        self.history = Some(history);
+    }
+
+    /// Stops hashing generations, `cycle` and `motion` are `None` again.
+    pub fn untrack_cycles(&mut self) {
+        self.history = None;
+    }
+
+    /// Cycle the board entered, if `track_cycles` is on and it repeated.
+    pub fn cycle(&self) -> Option<Cycle> {
+        return self.history.as_ref().and_then(|h| h.cycle());
+    }
+
+    /// Like `cycle` but the live cells may come back anywhere on the board,
+    /// e.g. a spaceship on a torus. Only for the board as a whole, see
+    /// `History::motion`.
+    pub fn motion(&self) -> Option<Motion> {
+        return self.history.as_ref().and_then(|h| h.motion());
+    }
+
+    /// Seed to replay the game with `Shape::Soup`, if it was created from a
+    /// soup or a `Shape::Random` board.
+    pub fn seed(&self) -> Option<u64> {
+        return self.seed;
+    }
+
+    /// Population, births, deaths and extent of the current generation.
+    pub fn statistics(&self) -> Statistics {
+        return self.statistics;
+    }
+
+    fn record(&mut self, mut statistics: Statistics) {
+        self.generation += 1;
+
+        let cells = match &self.packed {
+            Some(packed) => (packed.width * packed.height) as usize,
+            None => self.state().cells.len(),
         };
 
-        self.state = Arc::new(next_state);
+        statistics.finish(self.generation, cells);
+        self.statistics = statistics;
+
+        if let Some(history) = self.history.as_mut() {
+            let packed = &self.packed;
+            let state = self
+                .state
+                .get_or_init(|| packed.as_ref().unwrap().to_state());
+
+            history.record(self.generation, state);
+        }
+    }
+
+    /// Board of the current generation, unpacked first with the bit-packed
+    /// backend.
+    pub fn state(&self) -> &BoardState {
+        return self
+            .state
+            .get_or_init(|| self.packed.as_ref().unwrap().to_state());
+    }
+
+    /// Board to change from outside, e.g. to draw cells on it. The next
+    /// generation starts over from it.
+    pub fn state_mut(&mut self) -> &mut BoardState {
+        self.state();
+        self.stale = true;
+
+        return self.state.get_mut().unwrap();
+    }
+
+    /// Replaces the board, see `state_mut`.
+    pub fn set_state(&mut self, state: BoardState) {
+        self.state = OnceCell::from(state);
+        self.stale = true;
+    }
+
+    /// Everything has to be recomputed when the board was changed from
+    /// outside since the last generation.
+    fn sync(&mut self) {
+        if !self.stale {
+            return;
+        }
+
+        let state = self.state.get().unwrap();
+
+        if let Some(packed) = self.packed.as_mut() {
+            *packed = BitBoard::from_state(state);
+        }
+
+        self.dirty = Arc::new(vec![true; state.tiles()]);
+        self.statistics = Statistics::of(state, &self.rule, self.generation);
+        self.stale = false;
+
+        // Earlier generations say nothing about the new board.
+        if let Some(history) = self.history.as_mut() {
+            history.clear();
+            history.record(self.generation, state);
+        }
+    }
+
+    fn tick_packed(&mut self) {
+        let packed = self.packed.as_mut().unwrap();
+        let statistics = packed.step(&self.rule);
+
+        // Every tile is stepped.
+        self.active_tiles = self.dirty.len();
+        self.state = OnceCell::new();
+        self.record(statistics);
+    }
+
+    pub fn tick(&mut self) {
+        self.sync();
+
+        if self.packed.is_some() {
+            self.tick_packed();
+            return;
+        }
+
+        let state = self.state.get().unwrap();
+        let mut cells = vec![DEAD; state.cells.len()];
+        let mut changed = vec![false; state.tiles()];
+        let mut statistics = Statistics::default();
+        let (rule, dirty) = (&self.rule, &self.dirty);
+
+        //
+        // Each worker writes its own slice of the next generation, no
+        // copies and no locking. A worker panic is raised again here when the
+        // scope joins it.
+        //
+        let results = thread::scope(|scope| {
+            let mut rest = cells.as_mut_slice();
+            let mut workers = Vec::new();
+
+            for (from, to) in &self.chunk_intervals {
+                let (chunk, tail) = rest.split_at_mut(to - from);
+                let from = *from as i32;
+
+                rest = tail;
+                workers.push(scope.spawn(move || step_cells(state, rule, dirty, from, chunk)));
+            }
+
+            return workers
+                .into_iter()
+                .map(|worker| worker.join().unwrap())
+                .collect::<Vec<_>>();
+        });
+
+        for (chunk_changed, chunk_statistics) in results {
+            statistics.merge(&chunk_statistics);
+
+            for tile in chunk_changed {
+                changed[tile] = true;
+            }
+        }
+
+        self.finish_step(cells, &changed, statistics);
     }
 
     pub fn tickle(&mut self) {
-        let mut next_cells = vec![DEAD; (self.state.width * self.state.height) as usize];
-        let state = Arc::clone(&self.state);
+        self.sync();
 
-        for i in 0..next_cells.len() {
-            let i = i as i32;
+        if self.packed.is_some() {
+            self.tick_packed();
+            return;
+        }
 
-            let x = i % state.width;
-            let y = i / state.width;
+        let state = self.state.get().unwrap();
+        let mut next_cells = vec![DEAD; state.cells.len()];
+        let (changed_tiles, statistics) =
+            step_cells(state, &self.rule, &self.dirty, 0, &mut next_cells);
+        let mut changed = vec![false; state.tiles()];
 
-            let neighbors = state.get_neighbors(x, y);
+        for tile in changed_tiles {
+            changed[tile] = true;
+        }
+
+        self.finish_step(next_cells, &changed, statistics);
+    }
+
+    /// Installs the next generation and marks the tiles around the ones that
+    /// changed as dirty for the following one.
+    fn finish_step(&mut self, cells: Vec<u8>, changed: &[bool], statistics: Statistics) {
+        let state = self.state.get().unwrap();
+        let width = state.width;
+        let height = state.height;
+        let topology = state.topology;
+        let (tiles_x, tiles_y) = state.tiles_xy();
 
-            if state.cells[i as usize] == LIVE {
-                if neighbors <= 1 {
-                    // next_cells[i as usize] = DEAD;
-                } else if neighbors >= 4 {
-                    // next_cells[i as usize] = DEAD;
-                } else {
-                    next_cells[i as usize] = LIVE;
+        let mut dirty = vec![false; changed.len()];
+        let mut wrap_edges = false;
+
+        for (tile, _) in changed.iter().enumerate().filter(|(_, c)| **c) {
+            let tx = tile as i32 % tiles_x;
+            let ty = tile as i32 / tiles_x;
+
+            for ny in (ty - 1).max(0)..=(ty + 1).min(tiles_y - 1) {
+                for nx in (tx - 1).max(0)..=(tx + 1).min(tiles_x - 1) {
+                    dirty[(ny * tiles_x + nx) as usize] = true;
                 }
-            } else {
-                if neighbors == 3 {
-                    next_cells[i as usize] = LIVE;
-                } else {
-                    // next_cells[i as usize] = DEAD;
+            }
+
+            if tx == 0 || ty == 0 || tx == tiles_x - 1 || ty == tiles_y - 1 {
+                wrap_edges = true;
+            }
+        }
+
+        // Neighbors across a glued edge are always edge tiles.
+        if wrap_edges && topology != Topology::Bounded {
+            for ty in 0..tiles_y {
+                for tx in 0..tiles_x {
+                    if tx == 0 || ty == 0 || tx == tiles_x - 1 || ty == tiles_y - 1 {
+                        dirty[(ty * tiles_x + tx) as usize] = true;
+                    }
                 }
             }
         }
 
-        let width = self.state.width;
-        let height = self.state.height;
+        // Larger than Life neighbors can be past the tiles around a change.
+        if self.rule.range() > TILE_SIZE && changed.contains(&true) {
+            dirty = vec![true; changed.len()];
+        }
+
+        self.active_tiles = self.dirty.iter().filter(|d| **d).count();
+        self.dirty = Arc::new(dirty);
+
         let next_state = BoardState {
             width,
             height,
-            cells: next_cells,
+            cells,
+            topology,
         };
 
-        self.state = Arc::new(next_state);
+        self.state = OnceCell::from(next_state);
+        self.record(statistics);
     }
 }
 
-fn draw(state: &BoardState) {
+/// Writes the next generation of the cells from `from` on into `cells`, as
+/// many as it holds. Cells in clean tiles are copied over, returns the tiles
+/// where something changed and the statistics of the chunk.
+fn step_cells(
+    state: &BoardState,
+    rule: &Rule,
+    dirty: &[bool],
+    from: i32,
+    cells: &mut [u8],
+) -> (Vec<usize>, Statistics) {
+    let mut changed = Vec::new();
+    let mut statistics = Statistics::default();
+    let neighbors = rule.neighbors();
+    let (tiles_x, _) = state.tiles_xy();
+
+    for (i, out) in (from..).zip(cells.iter_mut()) {
+        let x = i % state.width;
+        let y = i / state.width;
+        let tile = ((y / TILE_SIZE) * tiles_x + x / TILE_SIZE) as usize;
+        let cell = state.cells[i as usize];
+
+        let next = if !dirty[tile] {
+            cell
+        } else if rule.colors() > 0 {
+            let (neighborhood, parents) = state.get_colored_neighborhood(x, y, &neighbors, rule);
+
+            rule.next_colored(cell, neighborhood, &parents)
+        } else {
+            rule.next(cell, state.get_neighborhood(x, y, &neighbors))
+        };
+
+        if next != cell {
+            statistics.add_change(rule.is_alive(cell), rule.is_alive(next));
+
+            if changed.last() != Some(&tile) {
+                changed.push(tile);
+            }
+        }
+
+        if rule.is_alive(next) {
+            statistics.add_live(x, y, next);
+        }
+
+        *out = next;
+    }
+
+    return (changed, statistics);
+}
+
+fn draw(state: &BoardState, rule: &Rule) {
     let mut line = String::from("");
+    let hexagonal = rule.neighborhood() == Neighborhood::Hexagonal;
 
     println!("");
     println!("");
@@ -246,10 +693,17 @@ fn draw(state: &BoardState) {
     println!("");
 
     for (i, cell) in state.cells.iter().enumerate() {
-        if *cell == LIVE {
-            line.push_str(LIVE_CHAR);
-        } else {
-            line.push_str(DEAD_CHAR);
+        // Hexagonal rows are half a cell right of the one below.
+        if hexagonal && i as i32 % state.width == 0 {
+            let y = i as i32 / state.width;
+
+            line.push_str(&" ".repeat((state.height - 1 - y) as usize));
+        }
+
+        match *cell {
+            DEAD => line.push_str(DEAD_CHAR),
+            _ if rule.is_alive(*cell) => line.push_str(LIVE_CHAR),
+            _ => line.push_str(DYING_CHAR),
         }
 
         if (i as i32 + 1) % state.width == 0 {
@@ -259,15 +713,18 @@ fn draw(state: &BoardState) {
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn start_life(
     width: i32,
     height: i32,
     init: Shape,
+    rule: Rule,
     n_workers: usize,
     limit: i64,
     wait: u64,
     debug: bool,
 ) {
+    let init = init.seeded();
     let sleep_time = time::Duration::from_millis(wait);
     let mut now;
     let mut game;
@@ -275,6 +732,10 @@ pub fn start_life(
     println!("\n\nConway's Game of Life\n");
     println!("Board:    {}x{}", width, height);
     println!("Cells:    {}", width * height);
+    if let Shape::Soup(soup) = &init {
+        println!("Seed:     {}", soup.seed);
+    }
+    println!("Rule:     {}", rule);
     println!("Workers:  {}", n_workers);
     println!("Max iter: {}", limit);
     println!("Wait:     {}ms", wait);
@@ -283,23 +744,385 @@ pub fn start_life(
 
     if debug {
         now = time::SystemTime::now();
-        game = Life::new(width, height, init, n_workers);
+        game = Life::new(width, height, init, n_workers, rule);
         println!("Generating Game ! {:?}\n", now.elapsed());
     } else {
-        game = Life::new(width, height, init, n_workers);
-        draw(&game.state);
+        game = Life::new(width, height, init, n_workers, rule);
+        draw(game.state(), &game.rule);
     }
 
+    game.track_cycles();
+
+    let mut moving = false;
+
     for _ in 0..limit {
         thread::sleep(sleep_time);
 
         if debug {
             now = time::SystemTime::now();
             game.tick();
-            println!("Tick ! {:?}", now.elapsed());
+            println!(
+                "Tick ! {:?} | Active tiles {}/{}",
+                now.elapsed(),
+                game.active_tiles(),
+                game.tiles()
+            );
         } else {
             game.tick();
-            draw(&game.state);
+            draw(game.state(), &game.rule);
+        }
+
+        println!("{}", game.statistics());
+
+        if game.rule.colors() > 0 {
+            let populations = game.statistics().populations;
+
+            println!("Colors {:?}", &populations[..game.rule.colors() as usize]);
+        }
+
+        if let (false, Some(motion)) = (moving, game.motion()) {
+            moving = motion.is_moving();
+
+            if moving {
+                println!("\nMoving ! {}", motion.velocity());
+            }
+        }
+
+        if let Some(cycle) = game.cycle() {
+            println!(
+                "\nStable ! Period {} since generation {}",
+                cycle.period, cycle.start
+            );
+            // Object codes only describe range 1 Moore rules.
+            if game.rule.neighborhood() == Neighborhood::Moore && game.rule.range() == 1 {
+                if let Some(census) = Census::new(game.state(), game.rule, CENSUS_MAX_PERIOD) {
+                    print!("\n{}", census);
+                }
+            }
+            break;
+        }
+    }
+}
+
+#[test]
+fn glider_wraps_on_torus() {
+    let mut game = Life::new(8, 8, Shape::Glider, 2, Rule::conway());
+    let initial = game.state().cells.clone();
+
+    game.set_topology(Topology::Torus);
+
+    // A glider moves one cell diagonally every 4 generations.
+    for _ in 0..(4 * 8) {
+        game.tickle();
+    }
+
+    assert_eq!(game.state().cells, initial);
+
+    for _ in 0..(4 * 8) {
+        game.tick();
+    }
+
+    assert_eq!(game.state().cells, initial);
+}
+
+#[test]
+fn active_tiles_match_full_step() {
+    for topology in [Topology::Bounded, Topology::Torus] {
+        let mut game = Life::new(200, 150, Shape::Random, 3, Rule::conway());
+        game.set_topology(topology);
+
+        let mut packed = BitBoard::from_state(game.state());
+
+        for i in 0..60 {
+            if i % 2 == 0 {
+                game.tick();
+            } else {
+                game.tickle();
+            }
+            packed.step(&Rule::conway());
+
+            assert_eq!(game.state().cells, packed.to_state().cells);
+        }
+    }
+
+    let mut game = Life::new(256, 256, Shape::Glider, 1, Rule::conway());
+
+    game.tickle();
+    assert_eq!(game.active_tiles(), game.tiles());
+
+    game.tickle();
+    assert_eq!(game.active_tiles(), 4);
+}
+
+#[test]
+fn bit_packed_unpacks_when_read() {
+    // Statistics come from the packed words.
+    let mut dense = Life::new(300, 200, Shape::Soup(Soup::new(9)), 1, Rule::conway());
+    let mut packed =
+        Life::from_state_with_backend(dense.state().clone(), 1, Rule::conway(), Backend::BitPacked);
+    for _ in 0..30 {
+        dense.tick();
+        packed.tick();
+        assert!(packed.state.get().is_none());
+        assert_eq!(packed.statistics(), dense.statistics());
+    }
+    assert_eq!(packed.state().cells, dense.state().cells);
+    packed.state_mut().cells[0] = LIVE;
+    packed.tick();
+    assert_eq!(packed.statistics().generation, 31);
+}
+
+#[test]
+fn cycles_are_detected() {
+    let cycle = |mut game: Life, generations: u64| {
+        game.track_cycles();
+
+        for _ in 0..generations {
+            game.tick();
         }
+
+        return game.cycle();
+    };
+
+    let blinker = Life::new(5, 5, Shape::Blinker, 1, Rule::conway());
+    assert_eq!(
+        cycle(blinker, 2),
+        Some(Cycle {
+            start: 0,
+            period: 2
+        })
+    );
+
+    let blinker = Life::new(5, 5, Shape::Blinker, 1, Rule::conway());
+    assert_eq!(cycle(blinker, 1), None);
+
+    // Turns into a block after one generation.
+    let corner = BoardState::from_rle("x = 2, y = 2\n2o$o!").unwrap();
+    let mut board = BoardState::new(6, 6, Shape::Empty);
+    board.place(&corner, 2, 2);
+    let game = Life::from_state_with_backend(board, 1, Rule::conway(), Backend::BitPacked);
+    assert_eq!(
+        cycle(game, 10),
+        Some(Cycle {
+            start: 1,
+            period: 1
+        })
+    );
+
+    // A glider needs 4 generations per cell to come back around a torus.
+    let mut glider = Life::new(8, 8, Shape::Glider, 2, Rule::conway());
+    glider.set_topology(Topology::Torus);
+    assert_eq!(
+        cycle(glider, 40),
+        Some(Cycle {
+            start: 0,
+            period: 32
+        })
+    );
+
+    // Replacing the board starts the history over.
+    let mut game = Life::new(5, 5, Shape::Blinker, 1, Rule::conway());
+    game.track_cycles();
+    game.tick();
+    game.set_state(BoardState::new(5, 5, Shape::Blinker));
+    game.tick();
+    assert_eq!(game.cycle(), None);
+    game.tick();
+    assert_eq!(
+        game.cycle(),
+        Some(Cycle {
+            start: 1,
+            period: 2
+        })
+    );
+    assert_eq!(game.generation(), 3);
+}
+
+#[test]
+fn generations_cells_decay() {
+    let brians_brain = Rule::parse("B2/S/C3").unwrap();
+    let pair = BoardState::from_rle("x = 2, y = 1\n2o!").unwrap();
+    let mut state = BoardState::new(6, 5, Shape::Empty);
+    state.place(&pair, 2, 2);
+
+    let mut game = Life::from_state(state, 2, brians_brain);
+    game.tick();
+
+    // The pair starts dying, the cells next to both of them are born.
+    let expected = [
+        (2, 1, LIVE),
+        (3, 1, LIVE),
+        (2, 2, 2),
+        (3, 2, 2),
+        (2, 3, LIVE),
+        (3, 3, LIVE),
+    ];
+    for (x, y, cell) in expected {
+        assert_eq!(game.state().get_index(x, y), cell, "cell ({}, {})", x, y);
+    }
+    assert_eq!(game.state().cells.iter().filter(|c| **c != DEAD).count(), 6);
+
+    let statistics = game.statistics();
+    assert_eq!(
+        (statistics.population, statistics.births, statistics.deaths),
+        (4, 4, 2)
+    );
+
+    // Dying cells don't count as neighbors and are gone a generation later.
+    game.tickle();
+    assert_eq!(game.state().get_index(2, 2), DEAD);
+    assert_eq!(game.state().get_index(2, 1), 2);
+    assert_eq!(game.statistics().deaths, 4);
+}
+
+#[test]
+fn neighborhoods_step() {
+    use crate::sparse::SparseLife;
+
+    let born = |rulestring: &str| {
+        let mut state = BoardState::new(5, 5, Shape::Empty);
+        state.cells[12] = LIVE;
+
+        let mut game = Life::from_state(state, 1, Rule::parse(rulestring).unwrap());
+        game.tick();
+
+        let mut cells: Vec<(i32, i32)> = (0..25)
+            .filter(|i| game.state().cells[*i as usize] == LIVE)
+            .map(|i| (i % 5 - 2, i / 5 - 2))
+            .collect();
+        cells.sort_unstable();
+
+        return cells;
+    };
+
+    assert_eq!(born("B1/S").len(), 8);
+    assert_eq!(born("B1/SV"), vec![(-1, 0), (0, -1), (0, 1), (1, 0)]);
+    assert_eq!(
+        born("B1/SH"),
+        vec![(-1, -1), (-1, 0), (0, -1), (0, 1), (1, 0), (1, 1)]
+    );
+    assert_eq!(born("R2,C0,M0,S12,B1,NN").len(), 12);
+
+    // Range 1 Larger than Life with the same counts is Life.
+    let soup = BoardState::new(40, 40, Shape::Soup(Soup::new(5)));
+    let mut life = Life::from_state(soup.clone(), 1, Rule::conway());
+    let mut ltl = Life::from_state(soup, 1, Rule::parse("R1,C0,M1,S3..4,B3,NM").unwrap());
+    for _ in 0..10 {
+        life.tick();
+        ltl.tick();
+    }
+    assert_eq!(ltl.state().cells, life.state().cells);
+
+    // Bosco's rule, a soup away from the edges of the board.
+    let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+    let mut state = BoardState::new(100, 100, Shape::Empty);
+    state.place(&BoardState::new(20, 20, Shape::Soup(Soup::new(8))), 40, 40);
+    let mut sparse = SparseLife::from_state(&state, bosco, 0, 0);
+    let mut game = Life::from_state(state, 2, bosco);
+    for _ in 0..5 {
+        game.tick();
     }
+    sparse.step(5);
+    assert!(game.state().cells.contains(&LIVE));
+    assert_eq!(
+        sparse.to_board_state(0, 0, 100, 100).cells,
+        game.state().cells
+    );
+}
+
+#[test]
+fn colored_births_follow_the_parents() {
+    use crate::soup::Soup;
+
+    let blinker = |rle: &str, rule: Rule| {
+        let mut state = BoardState::new(5, 5, Shape::Empty);
+        state.place(&BoardState::from_rle(rle).unwrap(), 1, 2);
+
+        let mut game = Life::from_state(state, 2, rule);
+        game.tick();
+
+        return game;
+    };
+
+    // The middle cell keeps its color, the others are born in the majority
+    // color of their 3 parents.
+    let immigration = blinker("x = 3, y = 1\nABA!", Rule::immigration());
+    let column: Vec<u8> = (1..4)
+        .map(|y| immigration.state().get_index(2, y))
+        .collect();
+    assert_eq!(column, vec![1, 2, 1]);
+    assert_eq!(immigration.statistics().populations, [2, 1, 0, 0]);
+
+    // 3 parents of different colors give the fourth one.
+    let quadlife = blinker("x = 3, y = 1\nABC!", Rule::quadlife());
+    let column: Vec<u8> = (1..4).map(|y| quadlife.state().get_index(2, y)).collect();
+    assert_eq!(column, vec![4, 2, 4]);
+    let statistics = quadlife.statistics();
+    assert_eq!(statistics.populations, [0, 1, 0, 2]);
+    assert_eq!(
+        (statistics.population, statistics.births, statistics.deaths),
+        (3, 2, 2)
+    );
+
+    // Colors don't change how the cells live and die.
+    let soup = Soup {
+        colors: 4,
+        ..Soup::new(5)
+    };
+    let mut colored = Life::new(40, 30, Shape::Soup(soup), 3, Rule::quadlife());
+    let mut plain = Life::new(40, 30, Shape::Soup(soup), 1, Rule::conway());
+    plain.set_state(BoardState {
+        cells: colored
+            .state()
+            .cells
+            .iter()
+            .map(|c| (*c > 0) as u8)
+            .collect(),
+        ..colored.state().clone()
+    });
+
+    for _ in 0..20 {
+        let previous = colored.state().clone();
+        colored.tick();
+        plain.tick();
+
+        let alive: Vec<u8> = colored
+            .state()
+            .cells
+            .iter()
+            .map(|c| (*c > 0) as u8)
+            .collect();
+        assert_eq!(alive, plain.state().cells);
+
+        let statistics = colored.statistics();
+        let mut expected = Statistics::between(&previous, colored.state(), &colored.rule);
+        expected.finish(colored.generation(), colored.state().cells.len());
+        assert_eq!(statistics, expected);
+        assert_eq!(
+            statistics.populations.iter().sum::<u64>(),
+            plain.statistics().population
+        );
+    }
+}
+
+#[test]
+fn random_shape_is_seeded() {
+    let soup = match Shape::Random.seeded() {
+        Shape::Soup(soup) => soup,
+        _ => panic!("random shapes are seeded as soups"),
+    };
+
+    let a = BoardState::new(30, 20, Shape::Soup(soup));
+    let b = BoardState::new(30, 20, Shape::Soup(Soup::new(soup.seed)));
+    assert_eq!(a.cells, b.cells);
+    assert!(matches!(Shape::Glider.seeded(), Shape::Glider));
+
+    let game = Life::new(30, 20, Shape::Random, 1, Rule::conway());
+    let seed = game.seed().unwrap();
+    let replay = BoardState::new(30, 20, Shape::Soup(Soup::new(seed)));
+    assert_eq!(game.state().cells, replay.cells);
+    assert_eq!(
+        Life::new(30, 20, Shape::Glider, 1, Rule::conway()).seed(),
+        None
+    );
 }
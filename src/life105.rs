@@ -0,0 +1,195 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, Shape, DEAD};
+use crate::pattern::{state_from_cells, Pattern, PatternError};
+use crate::rule::Rule;
+
+const HEADER: &str = "#Life 1.05";
+
+/// Parses a Life 1.05 file:
+///
+/// ```text
+/// #Life 1.05
+/// #D Glider
+/// #N
+/// #P -1 -1
+/// .*.
+/// ..*
+/// ***
+/// ```
+///
+/// Cells come in `#P x y` blocks of `.` and `*` rows, with (x, y) the block's
+/// top-left corner. `#N` is Conway's rule and `#R` sets another one in the
+/// "survival/birth" notation.
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::new(BoardState::new(0, 0, Shape::Empty));
+    let mut cells = Vec::new();
+    let mut block: Option<(i64, i64)> = None;
+
+    for (n, line) in text.lines().enumerate() {
+        let n = n + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next().unwrap_or(' ');
+            let value = chars.as_str().trim();
+
+            match kind {
+                'L' if n == 1 && line != HEADER => {
+                    return Err(PatternError::new(n, "expected \"#Life 1.05\" header"));
+                }
+                'D' | 'C' => {
+                    if let Some(name) = value.strip_prefix("Name:") {
+                        pattern.name = Some(name.trim().to_string());
+                    } else if let Some(author) = value.strip_prefix("Author:") {
+                        pattern.author = Some(author.trim().to_string());
+                    } else {
+                        pattern.comments.push(value.to_string());
+                    }
+                }
+                'N' => pattern.rule = Some(Rule::conway()),
+                'R' => {
+                    let rule =
+                        Rule::parse(value).map_err(|err| PatternError::new(n, &err.to_string()))?;
+                    pattern.rule = Some(rule);
+                }
+                'P' => {
+                    let mut fields = value.split_whitespace().map(|f| f.parse::<i64>());
+
+                    block = match (fields.next(), fields.next(), fields.next()) {
+                        (Some(Ok(x)), Some(Ok(y)), None) => Some((x, y)),
+                        _ => return Err(PatternError::new(n, "expected \"#P <x> <y>\"")),
+                    };
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let (x, y) = block.ok_or_else(|| PatternError::new(n, "cells outside a #P block"))?;
+
+        for (dx, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                '*' => cells.push((x + dx as i64, y)),
+                _ => {
+                    return Err(PatternError::new(
+                        n,
+                        &format!("unexpected character {:?}", c),
+                    ))
+                }
+            }
+        }
+
+        block = Some((x, y + 1));
+    }
+
+    pattern.state = state_from_cells(&cells)?;
+
+    return Ok(pattern);
+}
+
+/// Writes a pattern as Life 1.05, in a single block centered on (0, 0).
+pub fn write(pattern: &Pattern) -> String {
+    let state = &pattern.state;
+    let mut out = format!("{}\n", HEADER);
+
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("#D Name: {}\n", name));
+    }
+
+    if let Some(author) = &pattern.author {
+        out.push_str(&format!("#D Author: {}\n", author));
+    }
+
+    for comment in &pattern.comments {
+        out.push_str(&format!("#D {}\n", comment));
+    }
+
+    match pattern.rule {
+        // Life 1.05 only knows "S/B", other rules are written as "B/S".
+        Some(rule) if !rule.is_totalistic() || rule.states() > 2 => {
+            out.push_str(&format!("#R {}\n", rule));
+        }
+        Some(rule) if rule != Rule::conway() => {
+            let survival: String = (0..=8)
+                .filter(|n| rule.survives(*n))
+                .map(|n| n.to_string())
+                .collect();
+            let birth: String = (0..=8)
+                .filter(|n| rule.births(*n))
+                .map(|n| n.to_string())
+                .collect();
+
+            out.push_str(&format!("#R {}/{}\n", survival, birth));
+        }
+        _ => out.push_str("#N\n"),
+    }
+
+    out.push_str(&format!("#P {} {}\n", -state.width / 2, -state.height / 2));
+
+    for y in 0..state.height {
+        let row: String = state.cells[(y * state.width) as usize..((y + 1) * state.width) as usize]
+            .iter()
+            .map(|c| if *c == DEAD { '.' } else { '*' })
+            .collect();
+        let row = row.trim_end_matches('.');
+
+        // Empty rows still need a character to count as a line.
+        out.push_str(if row.is_empty() { "." } else { row });
+        out.push('\n');
+    }
+
+    return out;
+}
+
+impl BoardState {
+    pub fn from_life105(text: &str) -> Result<BoardState, PatternError> {
+        return Ok(parse(text)?.state);
+    }
+
+    pub fn to_life105(&self) -> String {
+        return write(&Pattern::new(self.clone()));
+    }
+}
+
+#[test]
+fn parse_and_write_life105() {
+    use crate::life::LIVE;
+
+    let text = "#Life 1.05\n\
+                #D Name: Glider\n\
+                #D The smallest spaceship.\n\
+                #N\n\
+                #P -1 -1\n\
+                .*\n\
+                ..*\n\
+                ***\n";
+    let pattern = parse(text).unwrap();
+
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.comments, vec!["The smallest spaceship."]);
+    assert_eq!(pattern.rule, Some(Rule::conway()));
+    assert_eq!(
+        pattern.state.cells,
+        BoardState::new(3, 3, Shape::Glider).cells
+    );
+    assert_eq!(write(&pattern), text);
+
+    // Blocks are placed relative to each other, with gaps between them.
+    let blocks = parse("#Life 1.05\n#R 23/36\n#P 0 0\n*\n.\n*\n#P 3 -1\n**\n").unwrap();
+    assert_eq!(blocks.rule, Some(Rule::parse("B36/S23").unwrap()));
+    assert_eq!((blocks.state.width, blocks.state.height), (5, 4));
+    assert_eq!(blocks.state.get_index(0, 1), LIVE);
+    assert_eq!(blocks.state.get_index(0, 3), LIVE);
+    assert_eq!(blocks.state.get_index(4, 0), LIVE);
+    assert!(write(&blocks).contains("#R 23/36\n"));
+
+    assert!(parse("#Life 1.06\n#P 0 0\n*\n").is_err());
+    assert!(parse("#Life 1.05\n*\n").is_err());
+}
@@ -0,0 +1,129 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, LIVE};
+use crate::pattern::{state_from_cells, PatternError};
+use crate::rule::Rule;
+use crate::sparse::SparseLife;
+
+const HEADER: &str = "#Life 1.06";
+
+/// Parses a Life 1.06 file, one "x y" live cell per line:
+///
+/// ```text
+/// #Life 1.06
+/// 0 -1
+/// 1 0
+/// -1 1
+/// 0 1
+/// 1 1
+/// ```
+pub fn parse(text: &str) -> Result<Vec<(i64, i64)>, PatternError> {
+    let mut cells = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let n = n + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if line.starts_with('#') {
+            if n == 1 && line != HEADER {
+                return Err(PatternError::new(n, "expected \"#Life 1.06\" header"));
+            }
+            continue;
+        }
+
+        let mut fields = line.split_whitespace().map(|f| f.parse::<i64>());
+
+        match (fields.next(), fields.next(), fields.next()) {
+            (Some(Ok(x)), Some(Ok(y)), None) => cells.push((x, y)),
+            _ => return Err(PatternError::new(n, "expected \"<x> <y>\"")),
+        }
+    }
+
+    return Ok(cells);
+}
+
+/// Writes live cells as Life 1.06, sorted by row.
+pub fn write<'a>(cells: impl Iterator<Item = &'a (i64, i64)>) -> String {
+    let mut cells: Vec<&(i64, i64)> = cells.collect();
+    let mut out = format!("{}\n", HEADER);
+
+    cells.sort_by_key(|(x, y)| (*y, *x));
+
+    for (x, y) in cells {
+        out.push_str(&format!("{} {}\n", x, y));
+    }
+
+    return out;
+}
+
+impl BoardState {
+    /// Board holding the bounding box of the cells, coordinates are made
+    /// relative to its top-left corner.
+    pub fn from_life106(text: &str) -> Result<BoardState, PatternError> {
+        return state_from_cells(&parse(text)?);
+    }
+
+    pub fn to_life106(&self) -> String {
+        let cells: Vec<(i64, i64)> = (0..self.cells.len())
+            .filter(|i| self.cells[*i] == LIVE)
+            .map(|i| {
+                (
+                    (i as i32 % self.width) as i64,
+                    (i as i32 / self.width) as i64,
+                )
+            })
+            .collect();
+
+        return write(cells.iter());
+    }
+}
+
+impl SparseLife {
+    pub fn from_life106(text: &str, rule: Rule) -> Result<SparseLife, PatternError> {
+        let mut game = SparseLife::new(rule);
+
+        for (x, y) in parse(text)? {
+            game.set(x, y, LIVE);
+        }
+
+        return Ok(game);
+    }
+
+    pub fn to_life106(&self) -> String {
+        return write(self.cells());
+    }
+}
+
+#[test]
+fn parse_and_write_life106() {
+    use crate::life::Shape;
+
+    let text = "#Life 1.06\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+    let game = SparseLife::from_life106(text, Rule::conway()).unwrap();
+
+    assert_eq!(game.population(), 5);
+    assert_eq!(game.get(-1, 1), LIVE);
+    assert_eq!(game.to_life106(), text);
+
+    let state = BoardState::from_life106(text).unwrap();
+    assert_eq!(state.cells, BoardState::new(3, 3, Shape::Glider).cells);
+    assert_eq!(state.to_life106(), "#Life 1.06\n1 0\n2 1\n0 2\n1 2\n2 2\n");
+
+    assert!(parse("#Life 1.05\n0 0\n").is_err());
+    assert!(parse("#Life 1.06\n0\n").is_err());
+    assert!(parse("#Life 1.06\n0 0 0\n").is_err());
+    // Too many cells for a board, whichever way it's spread.
+    assert_eq!(
+        BoardState::from_life106("#Life 1.06\n0 0\n100000 100000\n").unwrap_err(),
+        PatternError::new(1, "pattern too large for a board")
+    );
+    assert!(BoardState::from_life106("#Life 1.06\n0 0\n9223372036854775807 0\n").is_err());
+    assert!(BoardState::from_life106(
+        "#Life 1.06\n-9223372036854775808 0\n9223372036854775807 0\n"
+    )
+    .is_err());
+}
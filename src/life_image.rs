@@ -1,21 +1,90 @@
+#![allow(dead_code)]
+
 use base64::{engine::general_purpose::STANDARD, write::EncoderStringWriter};
 use image::ImageBuffer;
 use std::io::{Cursor, Write};
 
-use crate::life::{BoardState, LIVE};
+use crate::life::{BoardState, DEAD, LIVE};
+use crate::rule::{Neighborhood, Rule};
+use crate::ruletable::RuleTable;
+
+const LIVE_COLOR: [u8; 3] = [51, 51, 51];
+const DEAD_COLOR: [u8; 3] = [204, 204, 204];
+// First dying state of Generations rules, later ones fade towards dead.
+const DYING_COLOR: [u8; 3] = [230, 110, 40];
+// Live cells of colored rules, by color: Immigration uses the first 2.
+const OWNER_COLORS: [[u8; 3]; 4] = [[200, 40, 40], [40, 90, 200], [40, 160, 60], [220, 170, 20]];
+// Around the skewed rows of hexagonal boards.
+const MARGIN_COLOR: [u8; 4] = [255, 255, 255, 255];
+
+/// Color of a cell state, each state of the rule gets its own. Colored rules
+/// draw each owner in its own color.
+pub fn cell_color(cell: u8, rule: &Rule) -> image::Rgba<u8> {
+    let [r, g, b] = match cell {
+        DEAD => DEAD_COLOR,
+        _ if rule.colors() > 0 && rule.is_alive(cell) => OWNER_COLORS[cell as usize - 1],
+        LIVE => LIVE_COLOR,
+        _ => {
+            // Dying states are 2..states, never quite reaching dead.
+            let t = (cell - 2) as f32 / (rule.states() - 1) as f32;
+            let mut color = DYING_COLOR;
+
+            for (c, dead) in color.iter_mut().zip(DEAD_COLOR) {
+                *c = (*c as f32 + (dead as f32 - *c as f32) * t).round() as u8;
+            }
+
+            color
+        }
+    };
+
+    return image::Rgba([r, g, b, 255u8]);
+}
+
+pub fn draw_image_data_url(state: &BoardState, rule: &Rule) -> String {
+    let hexagonal = rule.neighborhood() == Neighborhood::Hexagonal;
+
+    return draw_cells(state, hexagonal, |cell| cell_color(cell, rule));
+}
+
+/// Like `draw_image_data_url`, in the table's `@COLORS`. States without a
+/// color get the ones `cell_color` gives Generations states.
+pub fn draw_table_image_data_url(state: &BoardState, table: &RuleTable) -> String {
+    let hexagonal = table.neighborhood == Neighborhood::Hexagonal;
+    let states = table.states.min(u8::MAX as u16) as u8;
+    let fallback = Rule::generations(&[], &[], states);
+
+    return draw_cells(state, hexagonal, |cell| match table.color(cell) {
+        Some([r, g, b]) => image::Rgba([r, g, b, 255u8]),
+        None => cell_color(cell, &fallback),
+    });
+}
 
-pub fn draw_image_data_url(state: &BoardState) -> String {
+fn draw_cells<F: Fn(u8) -> image::Rgba<u8>>(
+    state: &BoardState,
+    hexagonal: bool,
+    color: F,
+) -> String {
     // TODO: fix the casting. use u32 in BoardState width/height
     let width = state.width as u32;
     let height = state.height as u32;
 
-    let img = ImageBuffer::from_fn(width, height, |x, y| {
-        if state.get_index(x as i32, y as i32) == LIVE {
-            return image::Rgba([51u8, 51u8, 51u8, 255u8]);
-        } else {
-            return image::Rgba([204u8, 204u8, 204u8, 255u8]);
-        }
-    });
+    let img = if hexagonal {
+        // Cells 2 pixels wide, each row 1 pixel right of the one below, so
+        // every cell touches its 6 neighbors.
+        ImageBuffer::from_fn(width * 2 + height - 1, height, |x, y| {
+            let x = x as i32 - (height - 1 - y) as i32;
+
+            if x < 0 || x >= width as i32 * 2 {
+                return image::Rgba(MARGIN_COLOR);
+            }
+
+            return color(state.get_index(x / 2, y as i32));
+        })
+    } else {
+        ImageBuffer::from_fn(width, height, |x, y| {
+            return color(state.get_index(x as i32, y as i32));
+        })
+    };
 
     // TODO: Try to make all of this simpler add account for errors:
 
@@ -32,3 +101,61 @@ pub fn draw_image_data_url(state: &BoardState) -> String {
 
     return buf;
 }
+
+#[test]
+fn each_state_has_a_color() {
+    let star_wars = Rule::parse("345/2/4").unwrap();
+    let colors: Vec<image::Rgba<u8>> = (0..4).map(|cell| cell_color(cell, &star_wars)).collect();
+
+    for (i, color) in colors.iter().enumerate() {
+        assert!(!colors[..i].contains(color), "state {} repeats a color", i);
+    }
+
+    assert_eq!(
+        cell_color(LIVE, &Rule::conway()),
+        image::Rgba([51, 51, 51, 255])
+    );
+    assert_eq!(cell_color(2, &star_wars), image::Rgba([230, 110, 40, 255]));
+
+    let quadlife = Rule::quadlife();
+    let owners: Vec<image::Rgba<u8>> = (0..5).map(|cell| cell_color(cell, &quadlife)).collect();
+    for (i, color) in owners.iter().enumerate() {
+        assert!(!owners[..i].contains(color), "color {} repeats", i);
+    }
+    assert_eq!(cell_color(2, &Rule::immigration()), owners[2]);
+
+    // Rule tables draw in their own colors.
+    use base64::Engine;
+    let state = BoardState::from_rle("x = 2, y = 1\nAC!").unwrap();
+    let url = draw_table_image_data_url(&state, &RuleTable::wireworld());
+    let png = STANDARD
+        .decode(url.trim_start_matches("data:image/png;base64,"))
+        .unwrap();
+    let img = image::load_from_memory(&png).unwrap().to_rgba8();
+    assert_eq!(*img.get_pixel(0, 0), image::Rgba([0, 128, 255, 255]));
+    assert_eq!(*img.get_pixel(1, 0), image::Rgba([255, 128, 0, 255]));
+}
+
+#[test]
+fn hexagonal_rows_are_skewed() {
+    use crate::life::Shape;
+    use base64::Engine;
+    use image::GenericImageView;
+
+    let mut state = BoardState::new(5, 4, Shape::Empty);
+    state.cells[15] = LIVE;
+
+    let url = draw_image_data_url(&state, &Rule::parse("B2/S34H").unwrap());
+    let png = STANDARD
+        .decode(url.trim_start_matches("data:image/png;base64,"))
+        .unwrap();
+    let img = image::load_from_memory(&png).unwrap();
+
+    // 2 pixels per cell, plus 1 pixel of skew per row above the last one.
+    assert_eq!(img.dimensions(), (13, 4));
+    assert_eq!(img.get_pixel(0, 3), cell_color(LIVE, &Rule::conway()));
+    assert_eq!(img.get_pixel(1, 3), cell_color(LIVE, &Rule::conway()));
+    assert_eq!(img.get_pixel(2, 3), cell_color(DEAD, &Rule::conway()));
+    assert_eq!(img.get_pixel(0, 0), image::Rgba(MARGIN_COLOR));
+    assert_eq!(img.get_pixel(3, 0), cell_color(DEAD, &Rule::conway()));
+}
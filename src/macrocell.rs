@@ -0,0 +1,334 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::hashlife::{HashLife, NodeId, DEAD_LEAF, LIVE_LEAF};
+use crate::pattern::PatternError;
+use crate::rule::Rule;
+
+// Level of the 8x8 leaves Golly writes as rows of cells.
+const LEAF_LEVEL: u8 = 3;
+
+/// Parses a Golly macrocell file into a HashLife engine:
+///
+/// ```text
+/// [M2] (golly 4.2)
+/// #R B3/S23
+/// #G 0
+/// .*$..*$***$
+/// 4 0 0 0 1
+/// ```
+///
+/// Each line after the header defines a node, numbered from 1. 8x8 leaves
+/// are rows of `.` and `*` ended by `$`, bigger nodes are "level nw ne sw se"
+/// where 0 is the empty node. The last node is the root, centered on (0, 0).
+pub fn parse(text: &str) -> Result<HashLife, PatternError> {
+    let mut lines = text.lines().enumerate();
+
+    match lines.next() {
+        Some((_, line)) if line.starts_with("[M2]") => {}
+        _ => return Err(PatternError::new(1, "expected \"[M2]\" header")),
+    }
+
+    let mut rule = Rule::conway();
+    let mut generation = 0;
+    let mut nodes: Vec<NodeId> = Vec::new();
+    let mut game = HashLife::empty_engine(rule);
+
+    for (n, line) in lines {
+        let n = n + 1;
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(comment) = line.strip_prefix('#') {
+            let mut chars = comment.chars();
+            let kind = chars.next().unwrap_or(' ');
+            let value = chars.as_str().trim();
+
+            match kind {
+                'R' => {
+                    rule =
+                        Rule::parse(value).map_err(|err| PatternError::new(n, &err.to_string()))?;
+
+                    if rule.births(0) {
+                        return Err(PatternError::new(n, "B0 rules are not supported"));
+                    }
+
+                    if rule.colors() > 0 {
+                        return Err(PatternError::new(n, "colored rules are not supported"));
+                    }
+
+                    if rule.states() > 2 {
+                        return Err(PatternError::new(n, "Generations rules are not supported"));
+                    }
+
+                    if rule.range() > 1 {
+                        return Err(PatternError::new(
+                            n,
+                            "Larger than Life rules are not supported",
+                        ));
+                    }
+                }
+                'G' => {
+                    generation = value
+                        .parse::<u64>()
+                        .map_err(|_| PatternError::new(n, "invalid generation"))?;
+                }
+                _ => {}
+            }
+            continue;
+        }
+
+        let node = if line.starts_with(['.', '*', '$']) {
+            parse_leaf(&mut game, line, n)?
+        } else {
+            parse_node(&mut game, &nodes, line, n)?
+        };
+
+        nodes.push(node);
+    }
+
+    game.rule = rule;
+
+    let root = match nodes.last() {
+        Some(root) => *root,
+        None => game.empty(LEAF_LEVEL),
+    };
+    game.set_root(root, generation);
+
+    return Ok(game);
+}
+
+fn parse_leaf(game: &mut HashLife, line: &str, n: usize) -> Result<NodeId, PatternError> {
+    let mut cells = [[DEAD_LEAF; 8]; 8];
+    let (mut x, mut y) = (0, 0);
+
+    for c in line.chars() {
+        match c {
+            '$' => {
+                x = 0;
+                y += 1;
+                continue;
+            }
+            '.' | '*' => {}
+            _ => {
+                return Err(PatternError::new(
+                    n,
+                    &format!("unexpected character {:?}", c),
+                ))
+            }
+        }
+
+        if x >= 8 || y >= 8 {
+            return Err(PatternError::new(n, "leaf larger than 8x8"));
+        }
+
+        if c == '*' {
+            cells[y][x] = LIVE_LEAF;
+        }
+        x += 1;
+    }
+
+    return Ok(build_leaf(game, &cells, 0, 0, LEAF_LEVEL));
+}
+
+fn build_leaf(
+    game: &mut HashLife,
+    cells: &[[NodeId; 8]; 8],
+    x: usize,
+    y: usize,
+    level: u8,
+) -> NodeId {
+    if level == 0 {
+        return cells[y][x];
+    }
+
+    let half = 1 << (level - 1);
+    let nw = build_leaf(game, cells, x, y, level - 1);
+    let ne = build_leaf(game, cells, x + half, y, level - 1);
+    let sw = build_leaf(game, cells, x, y + half, level - 1);
+    let se = build_leaf(game, cells, x + half, y + half, level - 1);
+
+    return game.join(nw, ne, sw, se);
+}
+
+fn parse_node(
+    game: &mut HashLife,
+    nodes: &[NodeId],
+    line: &str,
+    n: usize,
+) -> Result<NodeId, PatternError> {
+    let fields = line
+        .split_whitespace()
+        .map(|f| f.parse::<usize>())
+        .collect::<Result<Vec<usize>, _>>()
+        .map_err(|_| PatternError::new(n, "malformed node"))?;
+
+    if fields.len() != 5 {
+        return Err(PatternError::new(n, "expected \"level nw ne sw se\""));
+    }
+
+    let level = fields[0];
+
+    if level == 0 || level > 63 {
+        return Err(PatternError::new(n, "invalid node level"));
+    }
+
+    let mut children = [DEAD_LEAF; 4];
+
+    for (child, field) in children.iter_mut().zip(&fields[1..]) {
+        *child = if level == 1 {
+            // Level 1 nodes hold cell states, as in multi-state files.
+            match field {
+                0 => DEAD_LEAF,
+                1 => LIVE_LEAF,
+                _ => return Err(PatternError::new(n, "cell state above 1")),
+            }
+        } else if *field == 0 {
+            game.empty(level as u8 - 1)
+        } else {
+            let id = nodes
+                .get(field - 1)
+                .copied()
+                .ok_or_else(|| PatternError::new(n, "reference to an undefined node"))?;
+
+            if game.node(id).level as usize != level - 1 {
+                return Err(PatternError::new(n, "child node of the wrong level"));
+            }
+            id
+        };
+    }
+
+    return Ok(game.join(children[0], children[1], children[2], children[3]));
+}
+
+/// Writes the engine as a macrocell file. Identical subtrees are written
+/// once, so the file grows with the number of distinct nodes rather than the
+/// area of the pattern.
+pub fn write(game: &HashLife) -> String {
+    let mut out = String::from("[M2] (life)\n");
+
+    out.push_str(&format!("#R {}\n", game.rule));
+
+    if game.generation() > 0 {
+        out.push_str(&format!("#G {}\n", game.generation()));
+    }
+
+    let mut lines = Vec::new();
+    let mut numbers = HashMap::new();
+
+    write_node(game, game.root(), &mut lines, &mut numbers);
+
+    // An empty root has no node lines, write a single empty leaf so the
+    // file still has a root.
+    if lines.is_empty() {
+        lines.push("$".to_string());
+    }
+
+    for line in lines {
+        out.push_str(&line);
+        out.push('\n');
+    }
+
+    return out;
+}
+
+/// Node number of a node, writing it and its children first when needed. 0
+/// is the empty node.
+fn write_node(
+    game: &HashLife,
+    id: NodeId,
+    lines: &mut Vec<String>,
+    numbers: &mut HashMap<NodeId, usize>,
+) -> usize {
+    let node = game.node(id);
+
+    if node.population == 0 {
+        return 0;
+    }
+
+    if let Some(number) = numbers.get(&id) {
+        return *number;
+    }
+
+    let line = if node.level == LEAF_LEVEL {
+        let mut line = String::new();
+
+        for y in 0..8 {
+            let row: String = (0..8)
+                .map(|x| if game.get_in(id, x, y) == 0 { '.' } else { '*' })
+                .collect();
+
+            line.push_str(row.trim_end_matches('.'));
+            line.push('$');
+        }
+
+        line.trim_end_matches('$').to_string() + "$"
+    } else if node.level < LEAF_LEVEL {
+        // Only reachable for roots smaller than a leaf, which the engine
+        // never builds.
+        unreachable!("node below leaf level")
+    } else {
+        let children = [node.nw, node.ne, node.sw, node.se]
+            .iter()
+            .map(|child| write_node(game, *child, lines, numbers).to_string())
+            .collect::<Vec<String>>();
+
+        format!("{} {}", node.level, children.join(" "))
+    };
+
+    lines.push(line);
+    numbers.insert(id, lines.len());
+
+    return lines.len();
+}
+
+impl HashLife {
+    pub fn from_macrocell(text: &str) -> Result<HashLife, PatternError> {
+        return parse(text);
+    }
+
+    pub fn to_macrocell(&self) -> String {
+        return write(self);
+    }
+}
+
+#[test]
+fn parse_and_write_macrocell() {
+    use crate::life::{BoardState, Shape};
+
+    let text = "[M2] (life)\n\
+                #R B3/S23\n\
+                .*$..*$***$\n\
+                4 0 0 0 1\n";
+    let game = parse(text).unwrap();
+    let glider = BoardState::new(3, 3, Shape::Glider);
+
+    assert_eq!(game.population(), 5);
+    assert_eq!(game.to_board_state(0, 0, 3, 3).cells, glider.cells);
+    assert_eq!(write(&game), text);
+
+    // Far past anything a dense board could hold, the file stays tiny.
+    let mut game = HashLife::new(&glider, Rule::conway());
+    game.step_pow2(40);
+
+    let text = game.to_macrocell();
+    let copy = HashLife::from_macrocell(&text).unwrap();
+    let offset = 1i64 << 38;
+
+    assert!(text.lines().count() < 50);
+    assert!(text.contains("#G 1099511627776"));
+    assert_eq!(copy.generation(), 1 << 40);
+    assert_eq!(copy.population(), 5);
+    assert_eq!(
+        copy.to_board_state(offset, offset, 3, 3).cells,
+        glider.cells
+    );
+
+    assert!(parse(".*$\n").is_err());
+    assert!(parse("[M2]\n4 0 0 0 1\n").is_err());
+    assert!(parse("[M2]\n#R B03/S23\n").is_err());
+}
@@ -1,8 +1,28 @@
 #![allow(dead_code)]
 #![allow(unused_imports)]
 
+mod apgcode;
+mod bitboard;
+mod catalogue;
+mod census;
+mod cycle;
+mod hashlife;
 mod life;
+mod life105;
+mod life106;
 mod life_image;
+mod macrocell;
+mod pattern;
+mod plaintext;
+mod recorder;
+mod rle;
+mod rule;
+mod ruletable;
+mod soup;
+mod sparse;
+mod statistics;
+mod topology;
+mod transform;
 
 use std::io::Read;
 use std::sync::{Arc, Mutex};
@@ -24,8 +44,13 @@ use futures::{
     stream::{SplitSink, SplitStream, StreamExt},
 };
 
+use hashlife::HashLife;
 use life::Life;
 use life_image::draw_image_data_url;
+use recorder::{Format, RecordOptions, Recorder};
+use rule::Rule;
+use soup::Soup;
+use topology::Topology;
 
 // use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
 
@@ -41,6 +66,13 @@ fn main1() {
         // life::Shape::Empty,
         life::Shape::Random,
         // life::Shape::Glider,
+        // rule
+        Rule::conway(),
+        // Rule::parse("B36/S23").unwrap(), // HighLife
+        // Rule::parse("B2/S/C3").unwrap(), // Brian's Brain
+        // Rule::parse("B3/S2-i34q").unwrap(), // tlife
+        // Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap(), // Bosco's rule
+        // Rule::quadlife(), // with a Soup { colors: 4, ..Soup::new(42) }
         // Worker threads
         1,
         // max iter
@@ -99,12 +131,18 @@ async fn main() {
     tokio::spawn(async move {
         // params
         let (width, height, init) = (5, 5, life::Shape::Blinker);
-        // let (width, height, init) = (100, 100, life::Shape::Random);
+        let rule = Rule::conway();
+        let topology = Topology::Torus;
         let n_workers = 1;
         let limit = 10_000;
         let wait = 1_000;
         let debug = false;
+        // Statistics timeline, written once `max_generations` went by.
+        let record_to: Option<&str> = None;
+        // let record_to = Some("stats.csv");
+        let record_options = RecordOptions::default();
         // END params
+        let init = init.seeded();
 
         let sleep_time = time::Duration::from_millis(wait);
         let mut now;
@@ -113,6 +151,11 @@ async fn main() {
         println!("\n\nConway's Game of Life\n");
         println!("Board:    {}x{}", width, height);
         println!("Cells:    {}", width * height);
+        if let life::Shape::Soup(soup) = &init {
+            println!("Seed:     {}", soup.seed);
+        }
+        println!("Rule:     {}", rule);
+        println!("Topology: {:?}", topology);
         println!("Workers:  {}", n_workers);
         println!("Max iter: {}", limit);
         println!("Wait:     {}ms", wait);
@@ -121,13 +164,34 @@ async fn main() {
 
         if debug {
             now = time::SystemTime::now();
-            game = Life::new(width, height, init, n_workers);
+            game = Life::new(width, height, init, n_workers, rule);
             println!("Generating Game ! {:?}\n", now.elapsed());
         } else {
-            game = Life::new(width, height, init, n_workers);
-            // draw(&game.state);
+            game = Life::new(width, height, init, n_workers, rule);
+            // draw(game.state());
+        }
+
+        game.set_topology(topology);
+        game.track_cycles();
+
+        let mut recorder = record_to.map(|path| {
+            let format = Format::from_path(path).unwrap_or(record_options.format);
+
+            return (
+                path,
+                Recorder::new(RecordOptions {
+                    format,
+                    ..record_options
+                }),
+            );
+        });
+
+        if let Some((_, recorder)) = recorder.as_mut() {
+            recorder.record(&game);
         }
 
+        let mut stable = false;
+        let mut moving = false;
         let mut ticker = tokio::time::interval(sleep_time);
 
         loop {
@@ -138,13 +202,18 @@ async fn main() {
 
                 game.tickle();
 
-                println!("Tick ! {:?}", now.elapsed());
+                println!(
+                    "Tick ! {:?} | Active tiles {}/{}",
+                    now.elapsed(),
+                    game.active_tiles(),
+                    game.tiles()
+                );
 
                 // let msg = format!("Tick ! {:?}", now.elapsed());
 
                 // let _ = tx_chan.send(msg);
 
-                let b = draw_image_data_url(&game.state);
+                let b = draw_image_data_url(game.state(), &game.rule);
 
                 // let _ = tx_chan.send(b);
 
@@ -156,9 +225,43 @@ async fn main() {
                 // println!("IMG {:?}", b);
             } else {
                 game.tickle();
-                let b = draw_image_data_url(&game.state);
+                let b = draw_image_data_url(game.state(), &game.rule);
                 let _ = tx_chan.send(b);
             }
+
+            let _ = tx_chan.send(format!("STATS: {}", game.statistics().to_json()));
+
+            if let Some((path, mut active)) = recorder.take() {
+                if active.record(&game) {
+                    recorder = Some((path, active));
+                } else {
+                    match active.save(path) {
+                        Ok(()) => {
+                            println!("Recorded {} samples to {}", active.samples().len(), path)
+                        }
+                        Err(err) => println!("Record error {:?}", err),
+                    }
+                }
+            }
+
+            // Keeps serving the board, only flags it once.
+            if let (false, Some(motion)) = (moving, game.motion()) {
+                moving = motion.is_moving();
+
+                if moving {
+                    println!("Moving ! {}", motion.velocity());
+                }
+            }
+
+            if let (false, Some(cycle)) = (stable, game.cycle()) {
+                stable = true;
+                println!(
+                    "Stable ! Period {} since generation {}",
+                    cycle.period, cycle.start
+                );
+                // Nothing left to find, stop hashing every generation.
+                game.untrack_cycles();
+            }
         }
     });
 
@@ -218,7 +321,13 @@ fn benchmark_new_life_random() {
         now = time::SystemTime::now();
 
         for _ in 0..10 {
-            Life::new(width, width, life::Shape::Random, n_workers);
+            Life::new(
+                width,
+                width,
+                life::Shape::Random,
+                n_workers,
+                Rule::default(),
+            );
         }
 
         let elapsed = now.elapsed().unwrap();
@@ -238,12 +347,18 @@ fn benchmark_draw_image_data_url() {
     let n_workers = 4;
 
     for width in vec![1, 10, 100, 1_000, 1_440, 10_000] {
-        game = Life::new(width, width, life::Shape::Random, n_workers);
+        game = Life::new(
+            width,
+            width,
+            life::Shape::Random,
+            n_workers,
+            Rule::default(),
+        );
 
         now = time::SystemTime::now();
 
         for _ in 0..10 {
-            draw_image_data_url(&game.state);
+            draw_image_data_url(game.state(), &game.rule);
         }
 
         let elapsed = now.elapsed().unwrap();
@@ -263,7 +378,13 @@ fn benchmark_life() {
     let n_workers = 4;
 
     for width in vec![1, 10, 100, 1_000, 1_440, 10_000] {
-        game = Life::new(width, width, life::Shape::Random, n_workers);
+        game = Life::new(
+            width,
+            width,
+            life::Shape::Random,
+            n_workers,
+            Rule::default(),
+        );
 
         now = time::SystemTime::now();
 
@@ -281,3 +402,118 @@ fn benchmark_life() {
         );
     }
 }
+
+#[test]
+fn benchmark_life_bitpacked() {
+    let mut now;
+    let mut game;
+    let n_workers = 4;
+
+    for width in vec![1, 10, 100, 1_000, 1_440, 10_000] {
+        game = Life::new_with_backend(
+            width,
+            width,
+            life::Shape::Random,
+            n_workers,
+            Rule::default(),
+            life::Backend::BitPacked,
+        );
+
+        now = time::SystemTime::now();
+
+        for _ in 0..10 {
+            game.tickle();
+        }
+
+        let elapsed = now.elapsed().unwrap();
+
+        println!(
+            "life-bitpacked-board-{width} | Total {:?} | Avg. {:?}",
+            elapsed,
+            elapsed / 10
+        );
+    }
+}
+
+#[test]
+fn benchmark_life_workers() {
+    let cores = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    for width in vec![100, 1_000, 1_440] {
+        let soup = life::Shape::Soup(Soup::new(42));
+        let mut baseline = Life::new(width, width, soup, 1, Rule::default());
+        let now = time::SystemTime::now();
+
+        for _ in 0..10 {
+            baseline.tickle();
+        }
+
+        let single = now.elapsed().unwrap();
+
+        println!(
+            "life-tickle-board-{width} | Total {:?} | Avg. {:?}",
+            single,
+            single / 10
+        );
+
+        for n_workers in vec![1, 2, 4, 8] {
+            let soup = life::Shape::Soup(Soup::new(42));
+            let mut game = Life::new(width, width, soup, n_workers, Rule::default());
+            let now = time::SystemTime::now();
+
+            for _ in 0..10 {
+                game.tick();
+            }
+
+            let elapsed = now.elapsed().unwrap();
+            let speedup = single.as_secs_f64() / elapsed.as_secs_f64();
+
+            println!(
+                "life-workers-{n_workers}-board-{width} | Total {:?} | Avg. {:?} | Speedup {:.2}x",
+                elapsed,
+                elapsed / 10,
+                speedup
+            );
+
+            assert_eq!(game.state().cells, baseline.state().cells);
+
+            // Only a machine with a core per worker can show the scaling,
+            // and only boards big enough to outweigh starting the workers.
+            if width == 1_440 && n_workers == 4 && cores >= 4 {
+                assert!(
+                    speedup > 1.5,
+                    "{} workers are only {:.2}x faster than tickle",
+                    n_workers,
+                    speedup
+                );
+            }
+        }
+    }
+
+    if cores < 4 {
+        println!("life-workers | {} core(s), scaling not checked", cores);
+    }
+}
+
+#[test]
+fn benchmark_hashlife() {
+    let mut now;
+    let mut game;
+    let state = life::BoardState::new(32, 32, life::Shape::Random);
+
+    for k in vec![4, 8, 16, 32, 48] {
+        game = HashLife::new(&state, Rule::default());
+
+        now = time::SystemTime::now();
+
+        game.step_pow2(k);
+
+        let elapsed = now.elapsed().unwrap();
+
+        println!(
+            "hashlife-soup-32-gen-2^{k} | Total {:?} | Population {}",
+            elapsed,
+            game.population()
+        );
+    }
+}
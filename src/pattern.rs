@@ -0,0 +1,95 @@
+use std::fmt;
+
+use crate::life::{BoardState, Shape, LIVE};
+use crate::rule::Rule;
+
+//
+// Pattern file contents: the cells plus whatever metadata the format carries.
+//
+#[derive(Clone, Debug)]
+pub struct Pattern {
+    pub name: Option<String>,
+    pub author: Option<String>,
+    pub comments: Vec<String>,
+    pub rule: Option<Rule>,
+    /// Name of a built-in rule table, for patterns of rules that aren't
+    /// rulestrings, e.g. "WireWorld". Takes over from `rule` when set.
+    pub rule_table: Option<String>,
+    pub state: BoardState,
+}
+
+impl Pattern {
+    pub fn new(state: BoardState) -> Pattern {
+        return Pattern {
+            name: None,
+            author: None,
+            comments: Vec::new(),
+            rule: None,
+            rule_table: None,
+            state,
+        };
+    }
+}
+
+// Most cells a board can hold, `BoardState` indexes them with an i32.
+pub const MAX_CELLS: u64 = i32::MAX as u64;
+
+/// Fails unless a board of `width` by `height` cells fits in a `BoardState`,
+/// `line` being where the size was read.
+pub fn check_size(width: u64, height: u64, line: usize) -> Result<(), PatternError> {
+    return match width.checked_mul(height) {
+        Some(cells) if cells <= MAX_CELLS && width <= MAX_CELLS && height <= MAX_CELLS => Ok(()),
+        _ => Err(PatternError::new(line, "pattern too large for a board")),
+    };
+}
+
+/// Board holding the bounding box of a list of live cells, for the formats
+/// that store coordinates. Coordinates become relative to its top-left
+/// corner.
+pub fn state_from_cells(cells: &[(i64, i64)]) -> Result<BoardState, PatternError> {
+    if cells.is_empty() {
+        return Ok(BoardState::new(0, 0, Shape::Empty));
+    }
+
+    let min_x = cells.iter().map(|(x, _)| *x).min().unwrap();
+    let min_y = cells.iter().map(|(_, y)| *y).min().unwrap();
+    let max_x = cells.iter().map(|(x, _)| *x).max().unwrap();
+    let max_y = cells.iter().map(|(_, y)| *y).max().unwrap();
+
+    let width = max_x.abs_diff(min_x).saturating_add(1);
+    let height = max_y.abs_diff(min_y).saturating_add(1);
+
+    check_size(width, height, 1)?;
+
+    let mut state = BoardState::new(width as i32, height as i32, Shape::Empty);
+
+    for (x, y) in cells {
+        let i = (y - min_y) as u64 * width + (x - min_x) as u64;
+        state.cells[i as usize] = LIVE;
+    }
+
+    return Ok(state);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PatternError {
+    pub line: usize,
+    pub message: String,
+}
+
+impl PatternError {
+    pub fn new(line: usize, message: &str) -> PatternError {
+        return PatternError {
+            line,
+            message: message.to_string(),
+        };
+    }
+}
+
+impl fmt::Display for PatternError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for PatternError {}
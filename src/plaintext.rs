@@ -0,0 +1,153 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, Shape, DEAD, LIVE};
+use crate::pattern::{Pattern, PatternError};
+
+/// Parses the plaintext `.cells` format as well as the repo's `state_*.txt`
+/// files. `.` is a dead cell, `O` or `*` a live one and lines starting with
+/// `!` are comments, "!Name: ..." and "!Author: ..." fill in the metadata.
+/// Short lines are padded with dead cells.
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::new(BoardState::new(0, 0, Shape::Empty));
+    let mut rows = Vec::new();
+
+    for (n, line) in text.lines().enumerate() {
+        let line = line.trim_end();
+
+        if let Some(comment) = line.strip_prefix('!') {
+            let comment = comment.strip_prefix(' ').unwrap_or(comment);
+
+            if let Some(name) = comment.strip_prefix("Name:") {
+                pattern.name = Some(name.trim().to_string());
+            } else if let Some(author) = comment.strip_prefix("Author:") {
+                pattern.author = Some(author.trim().to_string());
+            } else {
+                pattern.comments.push(comment.to_string());
+            }
+            continue;
+        }
+
+        let mut row = Vec::with_capacity(line.len());
+
+        for c in line.chars() {
+            row.push(match c {
+                '.' => DEAD,
+                'O' | '*' => LIVE,
+                _ => {
+                    return Err(PatternError::new(
+                        n + 1,
+                        &format!("unexpected character {:?}", c),
+                    ))
+                }
+            });
+        }
+
+        rows.push(row);
+    }
+
+    let width = rows.iter().map(|r| r.len()).max().unwrap_or(0);
+    let mut state = BoardState::new(width as i32, rows.len() as i32, Shape::Empty);
+
+    for (y, row) in rows.iter().enumerate() {
+        state.cells[y * width..y * width + row.len()].copy_from_slice(row);
+    }
+
+    pattern.state = state;
+
+    return Ok(pattern);
+}
+
+/// Writes a pattern in the `.cells` format.
+pub fn write_cells(pattern: &Pattern) -> String {
+    let mut out = String::new();
+
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("!Name: {}\n", name));
+    }
+
+    if let Some(author) = &pattern.author {
+        out.push_str(&format!("!Author: {}\n", author));
+    }
+
+    for comment in &pattern.comments {
+        out.push_str(&format!("!{}\n", comment));
+    }
+
+    out.push_str(&write_rows(&pattern.state, 'O'));
+
+    return out;
+}
+
+/// Writes a board in the `state_*.txt` format, `*` for live cells.
+pub fn write_state(state: &BoardState) -> String {
+    return write_rows(state, '*');
+}
+
+fn write_rows(state: &BoardState, live: char) -> String {
+    let mut out = String::with_capacity(state.cells.len() + state.height as usize);
+
+    for (i, cell) in state.cells.iter().enumerate() {
+        out.push(if *cell == DEAD { '.' } else { live });
+
+        if (i as i32 + 1) % state.width == 0 {
+            out.push('\n');
+        }
+    }
+
+    return out;
+}
+
+impl BoardState {
+    pub fn from_plaintext(text: &str) -> Result<BoardState, PatternError> {
+        return Ok(parse(text)?.state);
+    }
+
+    pub fn to_plaintext(&self) -> String {
+        return write_state(self);
+    }
+}
+
+#[test]
+fn parse_repo_state_files() {
+    for (text, width, height, population) in [
+        (include_str!("../state_beacon.txt"), 10, 10, 8),
+        (include_str!("../state_blinker.txt"), 5, 5, 3),
+        (include_str!("../state_glider.txt"), 44, 30, 5),
+        (include_str!("../state_penta_decathlon.txt"), 11, 18, 20),
+        (include_str!("../state_toad.txt"), 10, 10, 6),
+    ] {
+        let state = BoardState::from_plaintext(text).unwrap();
+
+        assert_eq!((state.width, state.height), (width, height));
+        assert_eq!(
+            state.cells.iter().filter(|c| **c == LIVE).count(),
+            population
+        );
+        assert_eq!(state.to_plaintext(), text);
+    }
+}
+
+#[test]
+fn parse_and_write_cells() {
+    let text = "!Name: Glider\n\
+                !Author: Richard K. Guy\n\
+                !The smallest spaceship.\n\
+                .O.\n\
+                ..O\n\
+                OOO\n";
+    let pattern = parse(text).unwrap();
+
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+    assert_eq!(pattern.comments, vec!["The smallest spaceship."]);
+    assert_eq!(
+        pattern.state.cells,
+        BoardState::new(3, 3, Shape::Glider).cells
+    );
+    assert_eq!(write_cells(&pattern), text);
+
+    let ragged = parse("O\n..O\n").unwrap();
+    assert_eq!(ragged.state.cells, vec![1, 0, 0, 0, 0, 1]);
+
+    assert!(parse("..x\n").is_err());
+}
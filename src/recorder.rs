@@ -0,0 +1,196 @@
+#![allow(dead_code)]
+
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+
+use crate::life::Life;
+use crate::statistics::Statistics;
+
+//
+// File format of a statistics timeline, one line per sample either way.
+//
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    /// Comma separated, with a header line.
+    #[default]
+    Csv,
+    /// One JSON object per line.
+    JsonLines,
+}
+
+impl Format {
+    /// Format for a file name: `.csv`, or `.json`, `.jsonl` and `.ndjson`
+    /// for JSON lines.
+    pub fn from_path<P: AsRef<Path>>(path: P) -> Option<Format> {
+        let extension = path.as_ref().extension()?.to_str()?.to_lowercase();
+
+        return match extension.as_str() {
+            "csv" => Some(Format::Csv),
+            "json" | "jsonl" | "ndjson" => Some(Format::JsonLines),
+            _ => None,
+        };
+    }
+}
+
+//
+// What to keep of a run.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecordOptions {
+    pub format: Format,
+    /// Keeps every `interval`th generation.
+    pub interval: u64,
+    /// Generations to record, counted from the first one recorded.
+    pub max_generations: u64,
+}
+
+impl Default for RecordOptions {
+    fn default() -> RecordOptions {
+        return RecordOptions {
+            format: Format::Csv,
+            interval: 1,
+            max_generations: 1000,
+        };
+    }
+}
+
+//
+// Statistics of a game over time, sampled as it runs.
+//
+#[derive(Clone, Debug, Default)]
+pub struct Recorder {
+    pub options: RecordOptions,
+    samples: Vec<Statistics>,
+    // Generation of the first call to `record`.
+    start: Option<u64>,
+}
+
+impl Recorder {
+    pub fn new(options: RecordOptions) -> Recorder {
+        assert!(options.interval > 0, "record interval must be at least 1");
+
+        return Recorder {
+            options,
+            samples: Vec::new(),
+            start: None,
+        };
+    }
+
+    /// Keeps the game's current statistics if the generation is sampled.
+    /// Call once per generation, returns false once `max_generations` went by
+    /// and nothing more will be kept.
+    pub fn record(&mut self, game: &Life) -> bool {
+        let statistics = game.statistics();
+        let start = *self.start.get_or_insert(statistics.generation);
+        let elapsed = statistics.generation - start;
+
+        if elapsed > self.options.max_generations {
+            return false;
+        }
+
+        if elapsed % self.options.interval == 0 {
+            self.samples.push(statistics);
+        }
+
+        return elapsed < self.options.max_generations;
+    }
+
+    /// Ticks the game until `max_generations` went by, recording each one.
+    pub fn run(&mut self, game: &mut Life) {
+        while self.record(game) {
+            game.tick();
+        }
+    }
+
+    pub fn samples(&self) -> &[Statistics] {
+        return &self.samples;
+    }
+
+    pub fn write<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        if self.options.format == Format::Csv {
+            writeln!(
+                out,
+                "generation,population,births,deaths,x,y,width,height,density,\
+                 population_1,population_2,population_3,population_4"
+            )?;
+        }
+
+        for s in &self.samples {
+            match self.options.format {
+                Format::Csv => writeln!(
+                    out,
+                    "{},{},{},{},{},{},{},{},{},{},{},{},{}",
+                    s.generation,
+                    s.population,
+                    s.births,
+                    s.deaths,
+                    s.bounding_box.x,
+                    s.bounding_box.y,
+                    s.bounding_box.width,
+                    s.bounding_box.height,
+                    s.density,
+                    s.populations[0],
+                    s.populations[1],
+                    s.populations[2],
+                    s.populations[3]
+                )?,
+                Format::JsonLines => writeln!(out, "{}", s.to_json())?,
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let mut out = BufWriter::new(File::create(path)?);
+
+        self.write(&mut out)?;
+
+        return out.flush();
+    }
+}
+
+#[test]
+fn record_a_run() {
+    use crate::life::Shape;
+    use crate::rule::Rule;
+
+    let mut game = Life::new(20, 20, Shape::Glider, 1, Rule::conway());
+    let mut recorder = Recorder::new(RecordOptions {
+        interval: 4,
+        max_generations: 10,
+        ..RecordOptions::default()
+    });
+
+    recorder.run(&mut game);
+
+    assert_eq!(game.generation(), 10);
+    let generations: Vec<u64> = recorder.samples().iter().map(|s| s.generation).collect();
+    assert_eq!(generations, vec![0, 4, 8]);
+    assert!(recorder.samples().iter().all(|s| s.population == 5));
+    assert!(!recorder.record(&game));
+
+    let mut csv = Vec::new();
+    recorder.write(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert_eq!(
+        lines[0],
+        "generation,population,births,deaths,x,y,width,height,density,\
+         population_1,population_2,population_3,population_4"
+    );
+    assert_eq!(lines[2], "4,5,2,2,1,1,3,3,0.0125,5,0,0,0");
+
+    recorder.options.format = Format::JsonLines;
+    let mut json = Vec::new();
+    recorder.write(&mut json).unwrap();
+    let json = String::from_utf8(json).unwrap();
+    assert_eq!(json.lines().count(), 3);
+    assert!(json.starts_with("{\"generation\":0,\"population\":5,"));
+
+    assert_eq!(Format::from_path("soup.CSV"), Some(Format::Csv));
+    assert_eq!(Format::from_path("out/soup.jsonl"), Some(Format::JsonLines));
+    assert_eq!(Format::from_path("soup"), None);
+}
@@ -0,0 +1,490 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, Shape, DEAD};
+use crate::pattern::{check_size, Pattern, PatternError, MAX_CELLS};
+use crate::rule::Rule;
+use crate::ruletable;
+use crate::topology::Topology;
+
+// Golly keeps RLE lines under 70 characters.
+const MAX_LINE_LEN: usize = 70;
+
+/// Parses a Run Length Encoded pattern:
+///
+/// ```text
+/// #N Glider
+/// #C A comment.
+/// x = 3, y = 3, rule = B3/S23
+/// bo$2bo$3o!
+/// ```
+///
+/// A bounded grid in the rule ("B3/S23:T80,38") sizes the board and sets its
+/// topology, with the pattern centered in it.
+pub fn parse(text: &str) -> Result<Pattern, PatternError> {
+    let mut pattern = Pattern::new(BoardState::new(0, 0, Shape::Empty));
+    let mut size = None;
+    let mut grid = None;
+    // Rows that have cells, by y. Empty rows take no memory, so a run of
+    // them can't exhaust it.
+    let mut rows: Vec<(usize, Vec<u8>)> = Vec::new();
+    let mut row: Vec<u8> = Vec::new();
+    let mut y = 0;
+    let mut width = 0;
+    let mut count: Option<usize> = None;
+    let mut prefix: Option<char> = None;
+    let mut done = false;
+
+    for (n, line) in text.lines().enumerate() {
+        let n = n + 1;
+        let line = line.trim();
+
+        if done {
+            break;
+        }
+
+        if line.is_empty() {
+            continue;
+        }
+
+        if size.is_none() {
+            if let Some(comment) = line.strip_prefix('#') {
+                let mut chars = comment.chars();
+                let kind = chars.next().unwrap_or(' ');
+                let value = chars.as_str().trim().to_string();
+
+                match kind {
+                    'N' => pattern.name = Some(value),
+                    'O' => pattern.author = Some(value),
+                    'C' | 'c' => pattern.comments.push(value),
+                    'r' => match ruletable::get(&value) {
+                        Some(table) => pattern.rule_table = Some(table.name),
+                        None => {
+                            let rule = Rule::parse(&value)
+                                .map_err(|err| PatternError::new(n, &err.to_string()))?;
+                            pattern.rule = Some(rule);
+                        }
+                    },
+                    _ => {}
+                }
+                continue;
+            }
+
+            if line.starts_with('x') {
+                let (width, height, rule, spec) = parse_header(line, n)?;
+
+                size = Some((width, height));
+                grid = spec;
+
+                match rule {
+                    Some(HeaderRule::Rule(rule)) => pattern.rule = Some(rule),
+                    Some(HeaderRule::Table(name)) => pattern.rule_table = Some(name),
+                    None => {}
+                }
+                continue;
+            }
+
+            return Err(PatternError::new(
+                n,
+                "expected \"x = <width>, y = <height>\" header",
+            ));
+        }
+
+        for c in line.chars() {
+            if c.is_ascii_whitespace() {
+                continue;
+            }
+
+            if let Some(digit) = c.to_digit(10) {
+                if prefix.is_some() {
+                    return Err(PatternError::new(n, "count inside a multi-state cell"));
+                }
+                count = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|c| c.checked_add(digit as usize))
+                    .filter(|c| *c as u64 <= MAX_CELLS);
+
+                if count.is_none() {
+                    return Err(PatternError::new(n, "run count too large for a board"));
+                }
+                continue;
+            }
+
+            let repeat = count.take().unwrap_or(1);
+
+            if c == '!' {
+                done = true;
+                break;
+            }
+
+            if c == '$' {
+                if !row.is_empty() {
+                    rows.push((y, std::mem::take(&mut row)));
+                }
+
+                y += repeat;
+                check_size(width.max(1) as u64, y as u64 + 1, n)?;
+                continue;
+            }
+
+            let cell = match (prefix.take(), c) {
+                (None, 'b') | (None, '.') => DEAD,
+                (None, 'o') => 1,
+                (None, 'A'..='X') => c as u8 - b'A' + 1,
+                (None, 'p'..='y') => {
+                    prefix = Some(c);
+                    count = Some(repeat);
+                    continue;
+                }
+                (Some(p), 'A'..='X') => {
+                    let state =
+                        (p as usize - 'p' as usize + 1) * 24 + (c as usize - 'A' as usize + 1);
+
+                    if state > 255 {
+                        return Err(PatternError::new(n, "cell state above 255"));
+                    }
+                    state as u8
+                }
+                _ => {
+                    return Err(PatternError::new(
+                        n,
+                        &format!("unexpected character {:?}", c),
+                    ))
+                }
+            };
+
+            // Checked before the row grows, the run may not fit.
+            width = width.max(row.len() + repeat);
+            check_size(width as u64, y as u64 + 1, n)?;
+
+            row.extend(std::iter::repeat(cell).take(repeat));
+        }
+    }
+
+    if !row.is_empty() {
+        rows.push((y, row));
+    }
+
+    let (header_width, header_height) =
+        size.ok_or_else(|| PatternError::new(1, "missing header"))?;
+
+    // Be lenient with headers smaller than the actual pattern.
+    let height = rows.last().map_or(0, |(y, _)| y + 1);
+    check_size(
+        width.max(header_width as usize) as u64,
+        height.max(header_height as usize) as u64,
+        1,
+    )?;
+    let width = header_width.max(width as i32);
+    let height = header_height.max(height as i32);
+
+    let mut state = match grid {
+        Some(spec) => BoardState::from_golly_grid(&spec)
+            .map_err(|err| PatternError::new(1, &err.to_string()))?,
+        None => BoardState::new(width, height, Shape::Empty),
+    };
+    if state.width < width || state.height < height {
+        return Err(PatternError::new(
+            1,
+            "pattern is larger than its bounded grid",
+        ));
+    }
+
+    let x0 = (state.width - width) / 2;
+    let y0 = (state.height - height) / 2;
+
+    for (y, row) in &rows {
+        for (x, cell) in row.iter().enumerate() {
+            let i = (*y as i32 + y0) * state.width + x as i32 + x0;
+            state.cells[i as usize] = *cell;
+        }
+    }
+
+    pattern.state = state;
+
+    return Ok(pattern);
+}
+
+// Rule of a header, a rulestring or a built-in rule table.
+enum HeaderRule {
+    Rule(Rule),
+    Table(String),
+}
+
+fn parse_header(
+    line: &str,
+    n: usize,
+) -> Result<(i32, i32, Option<HeaderRule>, Option<String>), PatternError> {
+    let error = |message: &str| PatternError::new(n, message);
+
+    // The rule may carry a bounded grid with its own comma, "B3/S23:T80,38",
+    // so it is split off first.
+    let (sizes, rule) = match line.find("rule") {
+        Some(i) => {
+            let value = line[i + 4..].trim_start();
+            let value = value
+                .strip_prefix('=')
+                .ok_or_else(|| error("expected \"rule = <rule>\""))?;
+
+            (&line[..i], Some(value.trim()))
+        }
+        None => (line, None),
+    };
+
+    let mut width = None;
+    let mut height = None;
+
+    for field in sizes.split(',') {
+        let field = field.trim();
+
+        if field.is_empty() {
+            continue;
+        }
+
+        let (key, value) = field
+            .split_once('=')
+            .ok_or_else(|| error("malformed header"))?;
+        let value = value
+            .trim()
+            .parse::<i32>()
+            .map_err(|_| error("invalid pattern size"))?;
+
+        match key.trim() {
+            "x" => width = Some(value),
+            "y" => height = Some(value),
+            _ => return Err(error(&format!("unknown header field {:?}", key.trim()))),
+        }
+    }
+
+    let width = width.ok_or_else(|| error("missing x"))?;
+    let height = height.ok_or_else(|| error("missing y"))?;
+
+    if width < 0 || height < 0 {
+        return Err(error("invalid pattern size"));
+    }
+
+    check_size(width as u64, height as u64, n)?;
+
+    let (rule, grid) = match rule {
+        Some(rule) => {
+            let (rule, grid) = match rule.split_once(':') {
+                Some((rule, grid)) => (rule, Some(grid.trim().to_string())),
+                None => (rule, None),
+            };
+
+            let rule = match ruletable::get(rule) {
+                Some(table) => HeaderRule::Table(table.name),
+                None => HeaderRule::Rule(Rule::parse(rule).map_err(|err| error(&err.to_string()))?),
+            };
+
+            (Some(rule), grid)
+        }
+        None => (None, None),
+    };
+
+    return Ok((width, height, rule, grid));
+}
+
+/// Writes a pattern as RLE. The rule defaults to B3/S23 and the board
+/// topology is added as a Golly bounded grid when it has one.
+pub fn write(pattern: &Pattern) -> String {
+    let state = &pattern.state;
+    let mut out = String::new();
+
+    if let Some(name) = &pattern.name {
+        out.push_str(&format!("#N {}\n", name));
+    }
+
+    if let Some(author) = &pattern.author {
+        out.push_str(&format!("#O {}\n", author));
+    }
+
+    for comment in &pattern.comments {
+        out.push_str(&format!("#C {}\n", comment));
+    }
+
+    let mut rule = match &pattern.rule_table {
+        Some(name) => name.clone(),
+        None => pattern.rule.unwrap_or_default().to_string(),
+    };
+
+    if state.topology != Topology::Bounded {
+        if let Some(spec) = state.topology.to_golly(state.width, state.height) {
+            rule = format!("{}:{}", rule, spec);
+        }
+    }
+
+    out.push_str(&format!(
+        "x = {}, y = {}, rule = {}\n",
+        state.width, state.height, rule
+    ));
+
+    let multi_state = state.cells.iter().any(|c| *c > 1);
+    let mut tokens = Vec::new();
+    let mut pending_rows = 0;
+
+    for y in 0..state.height {
+        let row = &state.cells[(y * state.width) as usize..((y + 1) * state.width) as usize];
+        let len = row.iter().rposition(|c| *c != DEAD).map_or(0, |i| i + 1);
+
+        if len == 0 {
+            pending_rows += 1;
+            continue;
+        }
+
+        if pending_rows > 0 && !tokens.is_empty() {
+            tokens.push(run(pending_rows, "$"));
+        }
+        pending_rows = 1;
+
+        let mut x = 0;
+
+        while x < len {
+            let cell = row[x];
+            let mut end = x;
+
+            while end < len && row[end] == cell {
+                end += 1;
+            }
+
+            tokens.push(run(end - x, &cell_symbol(cell, multi_state)));
+            x = end;
+        }
+    }
+
+    tokens.push("!".to_string());
+
+    let mut line_len = 0;
+
+    for token in tokens {
+        if line_len + token.len() > MAX_LINE_LEN {
+            out.push('\n');
+            line_len = 0;
+        }
+
+        line_len += token.len();
+        out.push_str(&token);
+    }
+
+    out.push('\n');
+
+    return out;
+}
+
+fn run(count: usize, symbol: &str) -> String {
+    return if count == 1 {
+        symbol.to_string()
+    } else {
+        format!("{}{}", count, symbol)
+    };
+}
+
+fn cell_symbol(cell: u8, multi_state: bool) -> String {
+    if !multi_state {
+        return if cell == DEAD { "b" } else { "o" }.to_string();
+    }
+
+    if cell == DEAD {
+        return ".".to_string();
+    }
+
+    let prefix = (cell as usize - 1) / 24;
+    let letter = char::from(b'A' + ((cell as usize - 1) % 24) as u8);
+
+    return if prefix == 0 {
+        letter.to_string()
+    } else {
+        format!("{}{}", char::from(b'p' + prefix as u8 - 1), letter)
+    };
+}
+
+impl BoardState {
+    pub fn from_rle(text: &str) -> Result<BoardState, PatternError> {
+        return Ok(parse(text)?.state);
+    }
+
+    pub fn to_rle(&self) -> String {
+        return write(&Pattern::new(self.clone()));
+    }
+}
+
+#[test]
+fn parse_and_write_rle() {
+    let text = "#N Glider\n\
+                #O Richard K. Guy\n\
+                #C The smallest spaceship.\n\
+                x = 3, y = 3, rule = B3/S23\n\
+                bo$2bo$3o!\n";
+    let pattern = parse(text).unwrap();
+
+    assert_eq!(pattern.name.as_deref(), Some("Glider"));
+    assert_eq!(pattern.author.as_deref(), Some("Richard K. Guy"));
+    assert_eq!(pattern.comments, vec!["The smallest spaceship."]);
+    assert_eq!(pattern.rule, Some(Rule::conway()));
+    assert_eq!(
+        pattern.state.cells,
+        BoardState::new(3, 3, Shape::Glider).cells
+    );
+    assert_eq!(write(&pattern), text);
+
+    // Gosper glider gun, with runs of empty rows and wrapped lines.
+    let gun = "x = 36, y = 9, rule = B3/S23\n\
+               24bo$22bobo$12b2o6b2o12b2o$11bo3bo4b2o12b2o$2o8bo5bo3b2o$2o8bo3bob2o4b\n\
+               obo$10bo5bo7bo$11bo3bo$12b2o!\n";
+    let pattern = parse(gun).unwrap();
+    assert_eq!(pattern.state.cells.iter().filter(|c| **c == 1).count(), 36);
+    assert_eq!(write(&pattern), gun);
+
+    let blank_rows = parse("x = 2, y = 4\no3$o!").unwrap();
+    assert_eq!(blank_rows.state.cells, vec![1, 0, 0, 0, 0, 0, 1, 0]);
+    assert_eq!(
+        blank_rows.state.to_rle(),
+        "x = 2, y = 4, rule = B3/S23\no3$o!\n"
+    );
+
+    let multi_state = parse("x = 4, y = 1, rule = B3/S23\n.A2pB!").unwrap();
+    assert_eq!(multi_state.state.cells, vec![0, 1, 26, 26]);
+    assert!(write(&multi_state).ends_with(".A2pB!\n"));
+
+    let wireworld = parse("x = 3, y = 1, rule = wireworld\nBAC!").unwrap();
+    assert_eq!(wireworld.rule_table.as_deref(), Some("WireWorld"));
+    assert_eq!(wireworld.state.cells, vec![2, 1, 3]);
+    assert!(write(&wireworld).contains("rule = WireWorld\n"));
+}
+
+#[test]
+fn parse_rle_bounded_grid() {
+    let pattern = parse("x = 3, y = 1, rule = B3/S23:T9,5\n3o!").unwrap();
+
+    assert_eq!(pattern.state.width, 9);
+    assert_eq!(pattern.state.height, 5);
+    assert_eq!(pattern.state.topology, Topology::Torus);
+    assert_eq!(pattern.state.get_index(3, 2), 1);
+    assert_eq!(pattern.state.get_index(5, 2), 1);
+    assert!(write(&pattern).contains("rule = B3/S23:T9,5"));
+
+    assert!(parse("x = 3, y = 1, rule = B3/S23:T2,2\n3o!").is_err());
+    assert!(parse("x = 3, y = 1, rule = B3/S99\n3o!").is_err());
+
+    // Sizes and runs beyond what a board holds are errors, not panics.
+    let too_large = |text: &str| parse(text).unwrap_err().message;
+    assert_eq!(
+        too_large("x = 70000, y = 70000\no!"),
+        "pattern too large for a board"
+    );
+    assert_eq!(
+        too_large("x = 1, y = 1\n99999999999999999999999o!"),
+        "run count too large for a board"
+    );
+    assert_eq!(
+        too_large("x = 1, y = 1\n3000000000o!"),
+        "run count too large for a board"
+    );
+    assert_eq!(
+        too_large("x = 1, y = 1\n100000o99999$o!"),
+        "pattern too large for a board"
+    );
+    assert!(parse("x = 1, y = 1, rule = B3/S23:T70000,70000\no!").is_err());
+    assert!(parse("3o!").is_err());
+    assert!(parse("x = 3, y = 1\n3z!").is_err());
+}
@@ -0,0 +1,963 @@
+use std::fmt;
+use std::str::FromStr;
+
+use crate::life::{DEAD, LIVE};
+use crate::transform::Transform;
+
+/// Offsets of the 8 neighbors, row by row from the top-left one. Bit `i` of
+/// a neighborhood is set when the neighbor at `NEIGHBORS[i]` is alive.
+pub const NEIGHBORS: [(i32, i32); 8] = [
+    (-1, -1),
+    (0, -1),
+    (1, -1),
+    (-1, 0),
+    (1, 0),
+    (-1, 1),
+    (0, 1),
+    (1, 1),
+];
+
+// Hensel notation letters for 1 to 4 live neighbors, with one neighborhood of
+// each, the rest are its rotations and reflections. Above 4 neighbors the
+// letter of a neighborhood is the one of its dead neighbors.
+const HENSEL: [&[(char, u8)]; 5] = [
+    &[],
+    &[('c', 0b00000001), ('e', 0b00000010)],
+    &[
+        ('c', 0b00000101),
+        ('e', 0b00001010),
+        ('a', 0b00000011),
+        ('i', 0b00011000),
+        ('k', 0b00010001),
+        ('n', 0b00100100),
+    ],
+    &[
+        ('c', 0b00100101),
+        ('e', 0b00011010),
+        ('a', 0b00001011),
+        ('i', 0b00000111),
+        ('k', 0b00110010),
+        ('n', 0b00001101),
+        ('j', 0b00001110),
+        ('q', 0b00100110),
+        ('r', 0b00011001),
+        ('y', 0b00110001),
+    ],
+    &[
+        ('c', 0b10100101),
+        ('e', 0b01011010),
+        ('a', 0b00001111),
+        ('i', 0b00011101),
+        ('k', 0b00110011),
+        ('n', 0b00100111),
+        ('j', 0b00111010),
+        ('q', 0b00110110),
+        ('r', 0b00011011),
+        ('y', 0b00110101),
+        ('t', 0b00111001),
+        ('w', 0b00101110),
+        ('z', 0b00111100),
+    ],
+];
+
+// Bit `n` is set for each of the 256 neighborhoods, see `NEIGHBORS`.
+type Table = [u64; 4];
+
+//
+// Cells that count as neighbors. Von Neumann and hexagonal neighborhoods are
+// subsets of `NEIGHBORS`, the hexagonal one leaves out the top-right and
+// bottom-left cells so each row is read as shifted half a cell to the right
+// of the one below it.
+//
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Neighborhood {
+    #[default]
+    Moore,
+    VonNeumann,
+    Hexagonal,
+    LargerThanLife(LargerThanLife),
+}
+
+//
+// Larger than Life: neighbors are the cells within `range`, in a square or
+// with `von_neumann` in a diamond, the cell itself included when `middle`.
+// Cells are born / survive when the number of live neighbors is within
+// `birth`/`survival`, both ends included.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LargerThanLife {
+    pub range: u8,
+    pub middle: bool,
+    pub von_neumann: bool,
+    pub birth: (u32, u32),
+    pub survival: (u32, u32),
+}
+
+impl Neighborhood {
+    /// Offsets of the neighbors, in the bit order of neighborhoods.
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        return match self {
+            Neighborhood::Moore => NEIGHBORS.to_vec(),
+            Neighborhood::VonNeumann => vec![(0, -1), (-1, 0), (1, 0), (0, 1)],
+            Neighborhood::Hexagonal => NEIGHBORS
+                .iter()
+                .copied()
+                .filter(|n| *n != (1, -1) && *n != (-1, 1))
+                .collect(),
+            Neighborhood::LargerThanLife(ltl) => {
+                let r = ltl.range as i32;
+                let mut offsets = Vec::new();
+
+                for dy in -r..=r {
+                    for dx in -r..=r {
+                        let inside = !ltl.von_neumann || dx.abs() + dy.abs() <= r;
+
+                        if inside && (ltl.middle || (dx, dy) != (0, 0)) {
+                            offsets.push((dx, dy));
+                        }
+                    }
+                }
+
+                offsets
+            }
+        };
+    }
+}
+
+//
+// Isotropic rule. A cell is born / survives when its neighborhood is set in
+// `birth`/`survival`. Life-like (totalistic) rules only depend on the number
+// of live neighbors, non-totalistic ones use Hensel notation to tell
+// neighborhoods apart, e.g. "B2-a/S12".
+//
+// Generations rules have more than 2 states: a live cell that doesn't survive
+// goes through the dying states 2, 3, ... up to `states - 1` before it's dead.
+// Dying cells don't count as neighbors and can't be born into.
+//
+// Colored rules, Immigration (2 colors) and QuadLife (4), have a live state
+// per color, 1 to `colors`. Live cells keep their color and are born in the
+// most common color of their parents.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rule {
+    birth: Table,
+    survival: Table,
+    states: u8,
+    neighborhood: Neighborhood,
+    colors: u8,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseRuleError {
+    rulestring: String,
+    reason: String,
+}
+
+impl fmt::Display for ParseRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid rule {:?}: {}", self.rulestring, self.reason)
+    }
+}
+
+impl std::error::Error for ParseRuleError {}
+
+impl Rule {
+    /// Conway's Game of Life, B3/S23.
+    pub fn conway() -> Rule {
+        return Rule::new(&[3], &[2, 3]);
+    }
+
+    pub fn new(birth: &[u8], survival: &[u8]) -> Rule {
+        return Rule::generations(birth, survival, 2);
+    }
+
+    /// Rule with `states - 2` dying states, e.g. Brian's Brain is
+    /// `Rule::generations(&[2], &[], 3)`.
+    pub fn generations(birth: &[u8], survival: &[u8], states: u8) -> Rule {
+        assert!(states >= 2);
+
+        let mut rule = Rule {
+            birth: [0; 4],
+            survival: [0; 4],
+            states,
+            neighborhood: Neighborhood::Moore,
+            colors: 0,
+        };
+
+        for neighborhood in 0..=255u8 {
+            let n = neighborhood.count_ones() as u8;
+
+            if birth.contains(&n) {
+                set(&mut rule.birth, neighborhood);
+            }
+
+            if survival.contains(&n) {
+                set(&mut rule.survival, neighborhood);
+            }
+        }
+
+        assert!(birth.iter().chain(survival).all(|n| *n <= 8));
+
+        return rule;
+    }
+
+    /// Immigration: Conway's Game of Life with 2 colors.
+    pub fn immigration() -> Rule {
+        return Rule::conway().with_colors(2);
+    }
+
+    /// QuadLife: Conway's Game of Life with 4 colors.
+    pub fn quadlife() -> Rule {
+        return Rule::conway().with_colors(4);
+    }
+
+    /// Same rule with 2 or 4 colors of live cells, see `birth_color`.
+    pub fn with_colors(self, colors: u8) -> Rule {
+        assert!(colors == 2 || colors == 4, "rules have 2 or 4 colors");
+        assert!(self.states == 2, "Generations rules can't have colors");
+
+        return Rule { colors, ..self };
+    }
+
+    /// Parses "B3/S23" style rulestrings as well as the legacy "23/3"
+    /// survival/birth notation. Generations rules add the number of states,
+    /// "B2/S/C3" or "345/2/4" (survival/birth/states). Counts may be followed
+    /// by Hensel letters to only include, or with a "-" exclude, some
+    /// neighborhoods: "B3/S2-i34q". A "V" or "H" suffix picks the von
+    /// Neumann or hexagonal neighborhood, "B2/S013V". Larger than Life rules
+    /// are written "R5,C0,M1,S34..58,B34..45,NM". "Immigration" and
+    /// "QuadLife" are Life with 2 and 4 colors, or any other rule when they
+    /// follow it, "B36/S23QuadLife".
+    pub fn parse(rulestring: &str) -> Result<Rule, ParseRuleError> {
+        let error = |reason: &str| ParseRuleError {
+            rulestring: rulestring.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let text = rulestring.trim();
+
+        if text.is_empty() {
+            return Err(error("empty rulestring"));
+        }
+
+        let upper = text.to_ascii_uppercase();
+
+        for (name, colors) in [("IMMIGRATION", 2), ("QUADLIFE", 4)] {
+            if let Some(rest) = upper.strip_suffix(name) {
+                let rule = match rest {
+                    "" => Rule::conway(),
+                    _ => Rule::parse(rest)?,
+                };
+
+                if rule.states > 2 {
+                    return Err(error("Generations rules can't have colors"));
+                }
+
+                return Ok(rule.with_colors(colors));
+            }
+        }
+
+        if upper.starts_with('R') && upper.contains(',') {
+            return parse_larger_than_life(&upper).map_err(|reason| error(&reason));
+        }
+
+        let (upper, neighborhood) = if let Some(rest) = upper.strip_suffix('V') {
+            (rest.to_string(), Neighborhood::VonNeumann)
+        } else if let Some(rest) = upper.strip_suffix('H') {
+            (rest.to_string(), Neighborhood::Hexagonal)
+        } else {
+            (upper, Neighborhood::Moore)
+        };
+
+        let mut birth = None;
+        let mut survival = None;
+        let mut states = None;
+
+        if upper.starts_with('B') || upper.starts_with('S') {
+            // "B3/S23", "S23/B3", "B3S23" or "B2/S/C3". A "C" is a Hensel
+            // letter unless it starts a section.
+            let mut parts = Vec::new();
+            let mut start = 0;
+
+            for (i, c) in upper.char_indices() {
+                let states = (c == 'C' || c == 'G') && upper[..i].ends_with('/');
+
+                if i > 0 && (c == 'B' || c == 'S' || states) {
+                    parts.push(&upper[start..i]);
+                    start = i;
+                }
+            }
+            parts.push(&upper[start..]);
+
+            for part in parts {
+                let part = part.trim_end_matches('/');
+
+                if part.starts_with('C') || part.starts_with('G') {
+                    if states.is_some() {
+                        return Err(error("duplicated section"));
+                    }
+                    states = Some(parse_states(&part[1..]).map_err(|reason| error(&reason))?);
+                    continue;
+                }
+
+                let digits = parse_hensel(&part[1..]).map_err(|reason| error(&reason))?;
+                let slot = if part.starts_with('B') {
+                    &mut birth
+                } else {
+                    &mut survival
+                };
+
+                if slot.is_some() {
+                    return Err(error("duplicated section"));
+                }
+                *slot = Some(digits);
+            }
+        } else {
+            // Legacy "S/B" notation, e.g. "23/3", or "S/B/C" for Generations.
+            let parts: Vec<&str> = upper.split('/').collect();
+
+            if parts.len() != 2 && parts.len() != 3 {
+                return Err(error(
+                    "expected \"B<digits>/S<digits>\" or \"<digits>/<digits>\"",
+                ));
+            }
+
+            survival = Some(parse_hensel(parts[0]).map_err(|reason| error(&reason))?);
+            birth = Some(parse_hensel(parts[1]).map_err(|reason| error(&reason))?);
+
+            if parts.len() == 3 {
+                states = Some(parse_states(parts[2]).map_err(|reason| error(&reason))?);
+            }
+        }
+
+        let rule = Rule {
+            birth: birth.unwrap_or([0; 4]),
+            survival: survival.unwrap_or([0; 4]),
+            states: states.unwrap_or(2),
+            neighborhood,
+            colors: 0,
+        };
+
+        if neighborhood != Neighborhood::Moore {
+            let size = neighborhood.offsets().len() as u8;
+
+            if !rule.is_totalistic() {
+                return Err(error("Hensel letters need the Moore neighborhood"));
+            }
+
+            if (size + 1..=8).any(|n| any_with(&rule.birth, n) || any_with(&rule.survival, n)) {
+                return Err(error(&format!("at most {} neighbors", size)));
+            }
+        }
+
+        return Ok(rule);
+    }
+
+    pub fn neighborhood(&self) -> Neighborhood {
+        return self.neighborhood;
+    }
+
+    /// How far the farthest neighbor is, along either axis.
+    pub fn range(&self) -> i32 {
+        return match self.neighborhood {
+            Neighborhood::LargerThanLife(ltl) => ltl.range as i32,
+            _ => 1,
+        };
+    }
+
+    /// Neighbors with the weight each adds to the neighborhood given to
+    /// `next` when alive: its bit, or 1 in Larger than Life where only the
+    /// count matters.
+    pub fn neighbors(&self) -> Vec<((i32, i32), u32)> {
+        let ltl = matches!(self.neighborhood, Neighborhood::LargerThanLife(_));
+
+        return self
+            .neighborhood
+            .offsets()
+            .into_iter()
+            .enumerate()
+            .map(|(i, offset)| (offset, if ltl { 1 } else { 1 << i }))
+            .collect();
+    }
+
+    /// Number of cell states, 2 for life-like rules.
+    pub fn states(&self) -> u8 {
+        if self.colors > 0 {
+            return self.colors + 1;
+        }
+
+        return self.states;
+    }
+
+    /// Number of colors of live cells, 0 unless Immigration or QuadLife.
+    pub fn colors(&self) -> u8 {
+        return self.colors;
+    }
+
+    /// Whether a cell in state `cell` is alive, and counts as a neighbor.
+    pub fn is_alive(&self, cell: u8) -> bool {
+        return cell == LIVE || (cell > LIVE && cell <= self.colors);
+    }
+
+    /// Whether only the number of live neighbors matters.
+    pub fn is_totalistic(&self) -> bool {
+        return (0..=8).all(|n| {
+            return (self.births(n) || !any_with(&self.birth, n))
+                && (self.survives(n) || !any_with(&self.survival, n));
+        });
+    }
+
+    /// Whether a cell is born with `neighbors` live neighbors, however they
+    /// are arranged.
+    pub fn births(&self, neighbors: u8) -> bool {
+        if let Neighborhood::LargerThanLife(ltl) = self.neighborhood {
+            return (ltl.birth.0..=ltl.birth.1).contains(&(neighbors as u32));
+        }
+
+        return all_with(&self.birth, neighbors);
+    }
+
+    /// Whether a cell survives with `neighbors` live neighbors, however they
+    /// are arranged.
+    pub fn survives(&self, neighbors: u8) -> bool {
+        if let Neighborhood::LargerThanLife(ltl) = self.neighborhood {
+            return (ltl.survival.0..=ltl.survival.1).contains(&(neighbors as u32));
+        }
+
+        return all_with(&self.survival, neighbors);
+    }
+
+    /// Next state of a cell given its current state and live neighbors, the
+    /// sum of their weights, see `neighbors`.
+    pub fn next(&self, cell: u8, neighborhood: u32) -> u8 {
+        let (born, survives) = match self.neighborhood {
+            Neighborhood::LargerThanLife(ltl) => (
+                (ltl.birth.0..=ltl.birth.1).contains(&neighborhood),
+                (ltl.survival.0..=ltl.survival.1).contains(&neighborhood),
+            ),
+            _ => (
+                get(&self.birth, neighborhood as u8),
+                get(&self.survival, neighborhood as u8),
+            ),
+        };
+
+        return match cell {
+            DEAD if born => LIVE,
+            DEAD => DEAD,
+            LIVE if survives => LIVE,
+            // Dying cells decay one state per generation.
+            _ if cell + 1 < self.states => cell + 1,
+            _ => DEAD,
+        };
+    }
+
+    /// Like `next` for colored rules, `parents[c - 1]` being the number of
+    /// live neighbors of color `c`.
+    pub fn next_colored(&self, cell: u8, neighborhood: u32, parents: &[u32]) -> u8 {
+        if self.is_alive(cell) {
+            return if self.next(LIVE, neighborhood) == LIVE {
+                cell
+            } else {
+                DEAD
+            };
+        }
+
+        return match self.next(cell, neighborhood) {
+            LIVE => self.birth_color(parents),
+            next => next,
+        };
+    }
+
+    /// Color of a cell born with `parents[c - 1]` parents of color `c`: the
+    /// most common one, the lowest on a tie. In QuadLife, 3 parents of
+    /// different colors give the fourth one.
+    pub fn birth_color(&self, parents: &[u32]) -> u8 {
+        let parents = &parents[..(self.colors as usize).min(parents.len())];
+
+        if self.colors == 4 && parents.iter().sum::<u32>() == 3 && parents.iter().all(|p| *p <= 1) {
+            if let Some(missing) = parents.iter().position(|p| *p == 0) {
+                return missing as u8 + 1;
+            }
+        }
+
+        let most = parents.iter().copied().max().unwrap_or(0);
+        let color = parents.iter().position(|p| *p == most).unwrap_or(0);
+
+        return color as u8 + 1;
+    }
+}
+
+fn get(table: &Table, neighborhood: u8) -> bool {
+    return table[neighborhood as usize / 64] & (1 << (neighborhood % 64)) != 0;
+}
+
+fn set(table: &mut Table, neighborhood: u8) {
+    table[neighborhood as usize / 64] |= 1 << (neighborhood % 64);
+}
+
+fn with_count(neighbors: u8) -> impl Iterator<Item = u8> {
+    return (0..=255u8).filter(move |c| c.count_ones() == neighbors as u32);
+}
+
+fn all_with(table: &Table, neighbors: u8) -> bool {
+    return with_count(neighbors).all(|c| get(table, c));
+}
+
+fn any_with(table: &Table, neighbors: u8) -> bool {
+    return with_count(neighbors).any(|c| get(table, c));
+}
+
+/// "R5,C0,M1,S34..58,B34..45,NM", C (states) defaults to 2, M (middle) to 0
+/// and N to M (Moore), the other neighborhood is N (von Neumann).
+fn parse_larger_than_life(text: &str) -> Result<Rule, String> {
+    let mut range = None;
+    let mut states = 2;
+    let mut middle = false;
+    let mut von_neumann = false;
+    let mut birth = None;
+    let mut survival = None;
+
+    for field in text.split(',').map(|f| f.trim()) {
+        let value = field.get(1..).unwrap_or("");
+
+        match field.chars().next() {
+            Some('R') => match value.parse::<u8>() {
+                Ok(r) if r > 0 => range = Some(r),
+                _ => return Err(format!("invalid range {:?}", value)),
+            },
+            Some('C') => {
+                states = match value.parse::<u8>() {
+                    Ok(0) => 2,
+                    _ => parse_states(value)?,
+                }
+            }
+            Some('M') => {
+                middle = match value {
+                    "0" => false,
+                    "1" => true,
+                    _ => return Err(format!("expected M0 or M1, got {:?}", field)),
+                }
+            }
+            Some('N') => {
+                von_neumann = match value {
+                    "M" => false,
+                    "N" => true,
+                    _ => return Err(format!("expected NM or NN, got {:?}", field)),
+                }
+            }
+            Some('B') => birth = Some(parse_count_range(value)?),
+            Some('S') => survival = Some(parse_count_range(value)?),
+            _ => return Err(format!("unexpected field {:?}", field)),
+        }
+    }
+
+    let range = range.ok_or("missing range")?;
+    let ltl = LargerThanLife {
+        range,
+        middle,
+        von_neumann,
+        birth: birth.ok_or("missing birth counts")?,
+        survival: survival.ok_or("missing survival counts")?,
+    };
+    let size = Neighborhood::LargerThanLife(ltl).offsets().len() as u32;
+
+    if ltl.birth.1 > size || ltl.survival.1 > size {
+        return Err(format!("at most {} neighbors", size));
+    }
+
+    return Ok(Rule {
+        birth: [0; 4],
+        survival: [0; 4],
+        states,
+        neighborhood: Neighborhood::LargerThanLife(ltl),
+        colors: 0,
+    });
+}
+
+/// "34..58", or a single count.
+fn parse_count_range(text: &str) -> Result<(u32, u32), String> {
+    let invalid = || format!("invalid count range {:?}", text);
+    let (low, high) = text.split_once("..").unwrap_or((text, text));
+    let low = low.parse::<u32>().map_err(|_| invalid())?;
+    let high = high.parse::<u32>().map_err(|_| invalid())?;
+
+    if low > high {
+        return Err(invalid());
+    }
+
+    return Ok((low, high));
+}
+
+/// Letters of the neighborhoods with `neighbors` live cells, each with one of
+/// its neighborhoods. Empty for 0 and 8 neighbors.
+fn letters(neighbors: u8) -> &'static [(char, u8)] {
+    return HENSEL[neighbors.min(8 - neighbors) as usize];
+}
+
+/// Hensel letter of a neighborhood, `None` with 0 or 8 live neighbors.
+fn hensel_letter(neighborhood: u8) -> Option<char> {
+    let n = neighborhood.count_ones();
+    let neighborhood = if n > 4 { !neighborhood } else { neighborhood };
+
+    return letters(n as u8)
+        .iter()
+        .find(|(_, c)| {
+            Transform::ALL
+                .iter()
+                .any(|t| transformed(*c, *t) == neighborhood)
+        })
+        .map(|(letter, _)| *letter);
+}
+
+/// Neighborhood rotated or reflected around the center cell.
+fn transformed(neighborhood: u8, transform: Transform) -> u8 {
+    let mut result = 0;
+
+    for (i, (dx, dy)) in NEIGHBORS.iter().enumerate() {
+        if neighborhood & (1 << i) != 0 {
+            let (x, y) = transform.apply(dx + 1, dy + 1, 3, 3);
+            let j = NEIGHBORS.iter().position(|n| *n == (x - 1, y - 1)).unwrap();
+
+            result |= 1 << j;
+        }
+    }
+
+    return result;
+}
+
+/// Counts, each optionally followed by Hensel letters, "2-a34q".
+fn parse_hensel(text: &str) -> Result<Table, String> {
+    let text = text.to_ascii_lowercase();
+    let mut table = [0; 4];
+    let mut chars = text.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let n = match c.to_digit(10) {
+            Some(n) if n <= 8 => n as u8,
+            _ => return Err(format!("unexpected character {:?}", c)),
+        };
+
+        let negated = chars.next_if_eq(&'-').is_some();
+        let mut only = String::new();
+
+        while let Some(letter) = chars.next_if(|c| c.is_ascii_alphabetic()) {
+            if !letters(n).iter().any(|(l, _)| *l == letter) {
+                return Err(format!("no letter {:?} for {} neighbors", letter, n));
+            }
+
+            only.push(letter);
+        }
+
+        if negated && only.is_empty() {
+            return Err(format!("expected letters after \"{}-\"", n));
+        }
+
+        for neighborhood in with_count(n) {
+            let listed = hensel_letter(neighborhood).is_some_and(|l| only.contains(l));
+
+            if only.is_empty() || listed != negated {
+                set(&mut table, neighborhood);
+            }
+        }
+    }
+
+    return Ok(table);
+}
+
+/// Counts of a table, with the Hensel letters of those that only include
+/// some neighborhoods, whichever of the letters in or out is shorter.
+fn format_hensel(table: &Table) -> String {
+    let mut text = String::new();
+
+    for n in 0..=8u8 {
+        if !any_with(table, n) {
+            continue;
+        }
+
+        text.push(char::from(b'0' + n));
+
+        if all_with(table, n) {
+            continue;
+        }
+
+        let (with, without): (Vec<char>, Vec<char>) =
+            letters(n).iter().map(|(l, _)| *l).partition(|l| {
+                return with_count(n).any(|c| get(table, c) && hensel_letter(c) == Some(*l));
+            });
+
+        if without.len() < with.len() {
+            text.push('-');
+            text.extend(without);
+        } else {
+            text.extend(with);
+        }
+    }
+
+    return text;
+}
+
+fn parse_states(text: &str) -> Result<u8, String> {
+    return match text.parse::<u8>() {
+        Ok(states) if states >= 2 => Ok(states),
+        _ => Err(format!(
+            "expected a number of states from 2 to 255, got {:?}",
+            text
+        )),
+    };
+}
+
+impl Default for Rule {
+    fn default() -> Rule {
+        return Rule::conway();
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    fn from_str(s: &str) -> Result<Rule, ParseRuleError> {
+        return Rule::parse(s);
+    }
+}
+
+impl fmt::Display for Rule {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.colors > 0 {
+            let name = if self.colors == 2 {
+                "Immigration"
+            } else {
+                "QuadLife"
+            };
+            let plain = Rule { colors: 0, ..*self };
+
+            if plain == Rule::conway() {
+                return write!(f, "{}", name);
+            }
+
+            return write!(f, "{}{}", plain, name);
+        }
+
+        if let Neighborhood::LargerThanLife(ltl) = self.neighborhood {
+            return write!(
+                f,
+                "R{},C{},M{},S{}..{},B{}..{},N{}",
+                ltl.range,
+                if self.states > 2 { self.states } else { 0 },
+                ltl.middle as u8,
+                ltl.survival.0,
+                ltl.survival.1,
+                ltl.birth.0,
+                ltl.birth.1,
+                if ltl.von_neumann { 'N' } else { 'M' }
+            );
+        }
+
+        write!(
+            f,
+            "B{}/S{}",
+            format_hensel(&self.birth),
+            format_hensel(&self.survival)
+        )?;
+
+        if self.states > 2 {
+            write!(f, "/C{}", self.states)?;
+        }
+
+        match self.neighborhood {
+            Neighborhood::VonNeumann => write!(f, "V"),
+            Neighborhood::Hexagonal => write!(f, "H"),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[test]
+fn parse_rulestrings() {
+    assert_eq!(Rule::parse("B3/S23").unwrap(), Rule::conway());
+    assert_eq!(Rule::parse("b3s23").unwrap(), Rule::conway());
+    assert_eq!(Rule::parse("S23/B3").unwrap(), Rule::conway());
+    assert_eq!(Rule::parse("23/3").unwrap(), Rule::conway());
+    assert_eq!(Rule::parse("B36/S23").unwrap(), Rule::new(&[3, 6], &[2, 3]));
+    assert_eq!(Rule::parse("B2/S").unwrap(), Rule::new(&[2], &[]));
+    assert_eq!(Rule::parse("/2").unwrap(), Rule::new(&[2], &[]));
+
+    assert!(Rule::parse("").is_err());
+    assert!(Rule::parse("B9/S23").is_err());
+    assert!(Rule::parse("B3/B3").is_err());
+    assert!(Rule::parse("23-3").is_err());
+
+    assert_eq!(Rule::parse("B36/S23").unwrap().to_string(), "B36/S23");
+    assert_eq!(Rule::parse("B2/S").unwrap().to_string(), "B2/S");
+}
+
+#[test]
+fn parse_generations_rules() {
+    let brians_brain = Rule::generations(&[2], &[], 3);
+
+    assert_eq!(Rule::parse("B2/S/C3").unwrap(), brians_brain);
+    assert_eq!(Rule::parse("b2s/g3").unwrap(), brians_brain);
+    assert_eq!(Rule::parse("/2/3").unwrap(), brians_brain);
+    assert_eq!(
+        Rule::parse("345/2/4").unwrap(),
+        Rule::generations(&[2], &[3, 4, 5], 4)
+    );
+    assert_eq!(Rule::parse("B3/S23/C2").unwrap(), Rule::conway());
+
+    assert!(Rule::parse("B2/S/C1").is_err());
+    assert!(Rule::parse("B2/S/C3/C4").is_err());
+    assert!(Rule::parse("345/2/x").is_err());
+
+    assert_eq!(brians_brain.to_string(), "B2/S/C3");
+    assert_eq!(brians_brain.states(), 3);
+    assert_eq!(Rule::conway().states(), 2);
+
+    // Live cells that don't survive go through the dying states.
+    let star_wars = Rule::parse("345/2/4").unwrap();
+    let (one, two, three) = (0b1, 0b11, 0b111);
+    assert_eq!(star_wars.next(LIVE, three), LIVE);
+    assert_eq!(star_wars.next(LIVE, one), 2);
+    assert_eq!(star_wars.next(2, two), 3);
+    assert_eq!(star_wars.next(3, two), DEAD);
+    assert_eq!(star_wars.next(DEAD, two), LIVE);
+    assert_eq!(Rule::conway().next(LIVE, one), DEAD);
+}
+
+#[test]
+fn parse_hensel_rules() {
+    // Neighborhood of the given neighbors, see `NEIGHBORS`.
+    let neighborhood = |cells: &[(i32, i32)]| {
+        return cells
+            .iter()
+            .map(|cell| 1 << NEIGHBORS.iter().position(|n| n == cell).unwrap())
+            .sum::<u32>();
+    };
+    let (n, ne, e, s, w) = ((0, -1), (1, -1), (1, 0), (0, 1), (-1, 0));
+
+    let rule = Rule::parse("B2-a/S12").unwrap();
+    assert!(!rule.is_totalistic());
+    assert_eq!(rule.next(DEAD, neighborhood(&[n, ne])), DEAD);
+    assert_eq!(rule.next(DEAD, neighborhood(&[e, ne])), DEAD);
+    assert_eq!(rule.next(DEAD, neighborhood(&[n, s])), LIVE);
+    assert_eq!(rule.next(LIVE, neighborhood(&[w])), LIVE);
+    assert_eq!(rule.to_string(), "B2-a/S12");
+
+    // Every letter of a count together is the totalistic rule.
+    assert_eq!(
+        Rule::parse("B3cekainyqjr/S2aceikn3").unwrap(),
+        Rule::conway()
+    );
+    assert_eq!(Rule::parse("B3/S23").unwrap().to_string(), "B3/S23");
+    assert!(Rule::conway().is_totalistic());
+
+    // Letters of 5 to 8 neighbors name the dead cells.
+    let rule = Rule::parse("B/S5i").unwrap();
+    assert_eq!(rule.next(LIVE, 0xff ^ neighborhood(&[w, e, n])), DEAD);
+    assert_eq!(
+        rule.next(LIVE, 0xff ^ neighborhood(&[(-1, -1), n, ne])),
+        LIVE
+    );
+
+    let tlife = Rule::parse("b3/s2-i34q").unwrap();
+    assert_eq!(tlife.to_string(), "B3/S2-i34q");
+    assert_eq!(tlife.next(LIVE, neighborhood(&[w, e])), DEAD);
+    assert_eq!(tlife.next(LIVE, neighborhood(&[n, e])), LIVE);
+    assert_eq!(Rule::parse("B2ce/S").unwrap().to_string(), "B2ce/S");
+    assert_eq!(
+        Rule::parse("B2cekai/S").unwrap().to_string(),
+        "B2-n/S",
+        "the shorter of the letters in and out is written"
+    );
+
+    // Every neighborhood gets the same result as its rotations and
+    // reflections.
+    let rule = Rule::parse("B2ci3ai4c8/S02ae3eijkq4iz5ar6i7e").unwrap();
+    for c in 0..=255u8 {
+        for transform in Transform::ALL {
+            assert_eq!(
+                rule.next(DEAD, c.into()),
+                rule.next(DEAD, transformed(c, transform).into())
+            );
+            assert_eq!(
+                rule.next(LIVE, c.into()),
+                rule.next(LIVE, transformed(c, transform).into())
+            );
+        }
+    }
+
+    assert!(Rule::parse("B2x/S23").is_err());
+    assert!(Rule::parse("B1k/S23").is_err());
+    assert!(Rule::parse("B2-/S23").is_err());
+    assert!(Rule::parse("B8c/S23").is_err());
+}
+
+#[test]
+fn parse_neighborhoods() {
+    let von_neumann = Rule::parse("B2/S013V").unwrap();
+    assert_eq!(von_neumann.neighborhood(), Neighborhood::VonNeumann);
+    assert_eq!(von_neumann.neighbors().len(), 4);
+    assert_eq!(von_neumann.to_string(), "B2/S013V");
+
+    let hexagonal = Rule::parse("b2/s34h").unwrap();
+    let offsets = hexagonal.neighborhood().offsets();
+    assert_eq!(offsets.len(), 6);
+    assert!(!offsets.contains(&(1, -1)) && !offsets.contains(&(-1, 1)));
+    assert_eq!(hexagonal.to_string(), "B2/S34H");
+    assert_eq!(
+        Rule::parse("B2/S/C3H").unwrap().to_string(),
+        "B2/S/C3H",
+        "Generations rules take a neighborhood too"
+    );
+
+    let bosco = Rule::parse("R5,C0,M1,S34..58,B34..45,NM").unwrap();
+    assert_eq!(bosco.range(), 5);
+    assert_eq!(bosco.neighbors().len(), 121);
+    assert!(bosco.neighbors().iter().all(|(_, weight)| *weight == 1));
+    assert_eq!(bosco.to_string(), "R5,C0,M1,S34..58,B34..45,NM");
+    assert!(bosco.births(34) && bosco.births(45) && !bosco.births(46));
+    assert_eq!(bosco.next(LIVE, 33), DEAD);
+    assert_eq!(bosco.next(LIVE, 58), LIVE);
+
+    let diamond = Rule::parse("R2,C3,S2..3,B3,NN").unwrap();
+    assert_eq!(diamond.neighbors().len(), 12);
+    assert_eq!(diamond.states(), 3);
+    assert_eq!(diamond.to_string(), "R2,C3,M0,S2..3,B3..3,NN");
+
+    assert!(Rule::parse("B5/S23V").is_err());
+    assert!(Rule::parse("B2a/S23H").is_err());
+    assert!(Rule::parse("R0,C0,M0,S2..3,B3..3,NM").is_err());
+    assert!(Rule::parse("R1,C0,M0,S2..3,NM").is_err());
+    assert!(Rule::parse("R1,C0,M0,S3..2,B3..3,NM").is_err());
+    assert!(Rule::parse("R1,C0,M0,S2..3,B3..9,NM").is_err());
+    assert!(Rule::parse("R1,C0,M0,S2..3,B3..3,NX").is_err());
+}
+
+#[test]
+fn parse_colored_rules() {
+    let quadlife = Rule::parse("QuadLife").unwrap();
+
+    assert_eq!(Rule::parse("immigration").unwrap(), Rule::immigration());
+    assert_eq!(quadlife, Rule::quadlife());
+    assert_eq!((quadlife.colors(), quadlife.states()), (4, 5));
+    assert_eq!(Rule::conway().colors(), 0);
+    assert_eq!(Rule::immigration().to_string(), "Immigration");
+
+    let highlife = Rule::parse("B36/S23QuadLife").unwrap();
+    assert_eq!(highlife, Rule::parse("B36/S23").unwrap().with_colors(4));
+    assert_eq!(highlife.to_string(), "B36/S23QuadLife");
+    assert!(Rule::parse("B2/S/C3Immigration").is_err());
+    assert!(Rule::parse("B9/S23Immigration").is_err());
+
+    // Colors are alive, dying states of Generations rules aren't.
+    assert!(quadlife.is_alive(4) && !quadlife.is_alive(DEAD) && !quadlife.is_alive(5));
+    assert!(!Rule::generations(&[2], &[], 3).is_alive(2));
+
+    // Births take the majority color, survivors keep theirs.
+    let three = 0b111;
+    assert_eq!(Rule::immigration().next_colored(DEAD, three, &[1, 2]), 2);
+    assert_eq!(Rule::immigration().next_colored(2, three, &[3, 0]), 2);
+    assert_eq!(Rule::immigration().next_colored(2, 0b1, &[1, 0]), DEAD);
+    assert_eq!(quadlife.next_colored(DEAD, three, &[0, 0, 3, 0]), 3);
+    assert_eq!(quadlife.next_colored(DEAD, three, &[1, 0, 1, 1]), 2);
+    assert_eq!(quadlife.next_colored(DEAD, three, &[1, 2, 0, 0]), 2);
+    assert_eq!(quadlife.next_colored(DEAD, 0b11, &[1, 1, 0, 0]), DEAD);
+}
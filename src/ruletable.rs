@@ -0,0 +1,689 @@
+#![allow(dead_code)]
+
+use std::collections::HashMap;
+
+use crate::life::BoardState;
+use crate::pattern::PatternError;
+use crate::rule::Neighborhood;
+
+/// Wireworld, as Golly ships it. 0 is empty, 1 an electron head, 2 an
+/// electron tail and 3 a conductor.
+pub const WIREWORLD: &str = "\
+@RULE WireWorld
+
+@TABLE
+n_states:4
+neighborhood:Moore
+symmetries:permute
+
+var a={0,1,2,3}
+var b=a
+var c=a
+var d=a
+var e=a
+var f=a
+var g=a
+var h=a
+var i={0,2,3}
+var j=i
+var k=i
+var l=i
+var m=i
+var n=i
+var o=i
+
+# Heads become tails, tails become wire.
+1,a,b,c,d,e,f,g,h,2
+2,a,b,c,d,e,f,g,h,3
+# Wire becomes a head next to one or two heads.
+3,1,i,j,k,l,m,n,o,1
+3,1,1,i,j,k,l,m,n,1
+
+@COLORS
+0 48 48 48
+1 0 128 255
+2 255 255 255
+3 255 128 0
+";
+
+// Rule tables that come with the crate, by name.
+const TABLES: [(&str, &str); 1] = [("WireWorld", WIREWORLD)];
+
+pub fn names() -> impl Iterator<Item = &'static str> {
+    return TABLES.iter().map(|(name, _)| *name);
+}
+
+/// Built-in rule table by name, ignoring case.
+pub fn get(name: &str) -> Option<RuleTable> {
+    let (_, text) = TABLES
+        .iter()
+        .find(|(n, _)| n.eq_ignore_ascii_case(name.trim()))?;
+
+    // The built-in tables are checked by the tests below, they always parse.
+    return Some(RuleTable::parse(text).unwrap());
+}
+
+/// Neighbors in the order transitions list them, clockwise from north.
+pub fn ring(neighborhood: Neighborhood) -> &'static [(i32, i32)] {
+    return match neighborhood {
+        Neighborhood::VonNeumann => &[(0, -1), (1, 0), (0, 1), (-1, 0)],
+        Neighborhood::Hexagonal => &[(0, -1), (1, 0), (1, 1), (0, 1), (-1, 0), (-1, -1)],
+        _ => &[
+            (0, -1),
+            (1, -1),
+            (1, 0),
+            (1, 1),
+            (0, 1),
+            (-1, 1),
+            (-1, 0),
+            (-1, -1),
+        ],
+    };
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Slot {
+    State(u8),
+    Variable(usize),
+}
+
+//
+// One line of a table: the cell, its neighbors in ring order, then the state
+// it becomes. A variable stands for the same state everywhere in a line.
+//
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Transition {
+    inputs: Vec<Slot>,
+    output: Slot,
+}
+
+//
+// Golly rule table: cells take one of `states` states and change by the first
+// transition matching their neighborhood, or stay as they are if none does.
+//
+#[derive(Clone, Debug)]
+pub struct RuleTable {
+    pub name: String,
+    /// Number of states, 0 to `states - 1`.
+    pub states: u16,
+    /// Moore, von Neumann or hexagonal.
+    pub neighborhood: Neighborhood,
+    // Ring orders a transition is also tried in, from the symmetries. Each
+    // maps a position of the transition to a position of the ring.
+    orders: Vec<Vec<usize>>,
+    // Neighbors match in any order.
+    permute: bool,
+    // States each variable stands for.
+    variables: Vec<Vec<u8>>,
+    transitions: Vec<Transition>,
+    colors: Vec<Option<[u8; 3]>>,
+}
+
+impl RuleTable {
+    /// Parses a `.rule` file. Only the `@RULE`, `@TABLE` and `@COLORS`
+    /// sections are read, the others are skipped.
+    pub fn parse(text: &str) -> Result<RuleTable, PatternError> {
+        let mut name = None;
+        let mut section = String::new();
+        let mut states: Option<u16> = None;
+        let mut neighborhood = Neighborhood::Moore;
+        let mut symmetries = ("none".to_string(), 0);
+        let mut names: Vec<String> = Vec::new();
+        let mut variables: Vec<Vec<u8>> = Vec::new();
+        let mut transitions = Vec::new();
+        let mut colors = Vec::new();
+
+        for (n, line) in text.lines().enumerate() {
+            let n = n + 1;
+            let line = line.split('#').next().unwrap().trim();
+            let error = |message: &str| PatternError::new(n, message);
+
+            if line.is_empty() {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('@') {
+                let (kind, value) = header.split_once(' ').unwrap_or((header, ""));
+
+                section = kind.to_string();
+                if section == "RULE" {
+                    name = Some(value.trim().to_string());
+                }
+                continue;
+            }
+
+            match section.as_str() {
+                "TABLE" => {}
+                "COLORS" => {
+                    colors.push((n, line.to_string()));
+                    continue;
+                }
+                _ => continue,
+            }
+
+            if let Some(definition) = line.strip_prefix("var ") {
+                let (variable, values) = definition
+                    .split_once('=')
+                    .ok_or_else(|| error("expected \"var <name>={<states>}\""))?;
+                let values = values.trim();
+                let values = values
+                    .strip_prefix('{')
+                    .and_then(|v| v.strip_suffix('}'))
+                    .unwrap_or(values);
+                let mut allowed = Vec::new();
+
+                for value in values.split(',').map(str::trim) {
+                    match names.iter().position(|v| v == value) {
+                        Some(i) => allowed.extend_from_slice(&variables[i]),
+                        None => allowed.push(
+                            value
+                                .parse()
+                                .map_err(|_| error(&format!("unknown state {:?}", value)))?,
+                        ),
+                    }
+                }
+
+                names.push(variable.trim().to_string());
+                variables.push(allowed);
+                continue;
+            }
+
+            if let Some((key, value)) = line.split_once(':') {
+                let value = value.trim();
+
+                match key.trim() {
+                    "n_states" => {
+                        let n_states = value
+                            .parse::<u16>()
+                            .ok()
+                            .filter(|s| (2..=256).contains(s))
+                            .ok_or_else(|| error("n_states must be between 2 and 256"))?;
+
+                        states = Some(n_states);
+                    }
+                    "neighborhood" => {
+                        neighborhood = match value {
+                            "Moore" => Neighborhood::Moore,
+                            "vonNeumann" => Neighborhood::VonNeumann,
+                            "hexagonal" => Neighborhood::Hexagonal,
+                            _ => {
+                                return Err(error(&format!("unsupported neighborhood {:?}", value)))
+                            }
+                        };
+                    }
+                    "symmetries" => symmetries = (value.to_string(), n),
+                    _ => return Err(error(&format!("unknown setting {:?}", key.trim()))),
+                }
+                continue;
+            }
+
+            // Either comma separated, or one digit per state when every
+            // state is below 10.
+            let tokens: Vec<String> = if line.contains(',') {
+                line.split(',').map(|t| t.trim().to_string()).collect()
+            } else {
+                line.chars().map(|c| c.to_string()).collect()
+            };
+
+            if tokens.len() != ring(neighborhood).len() + 2 {
+                return Err(error(&format!(
+                    "expected {} states in a transition",
+                    ring(neighborhood).len() + 2
+                )));
+            }
+
+            let mut slots = Vec::new();
+
+            for token in &tokens {
+                let slot = match names.iter().position(|v| v == token) {
+                    Some(i) => Slot::Variable(i),
+                    None => Slot::State(
+                        token
+                            .parse()
+                            .map_err(|_| error(&format!("unknown state {:?}", token)))?,
+                    ),
+                };
+
+                slots.push(slot);
+            }
+
+            let output = slots.pop().unwrap();
+
+            if let Slot::Variable(i) = output {
+                if !slots.contains(&output) {
+                    return Err(error(&format!(
+                        "output variable {:?} isn't bound by the inputs",
+                        names[i]
+                    )));
+                }
+            }
+
+            transitions.push((
+                n,
+                Transition {
+                    inputs: slots,
+                    output,
+                },
+            ));
+        }
+
+        let n_states = states.ok_or_else(|| PatternError::new(1, "missing n_states"))?;
+        let check = |n: usize, state: u8| {
+            if state as u16 >= n_states {
+                return Err(PatternError::new(
+                    n,
+                    &format!("state {} out of range", state),
+                ));
+            }
+
+            return Ok(state);
+        };
+
+        for (n, transition) in &transitions {
+            for slot in transition.inputs.iter().chain([&transition.output]) {
+                match slot {
+                    Slot::State(state) => {
+                        check(*n, *state)?;
+                    }
+                    Slot::Variable(i) => {
+                        for state in &variables[*i] {
+                            check(*n, *state)?;
+                        }
+                    }
+                }
+            }
+        }
+
+        let (orders, permute) = symmetry_orders(&symmetries.0, ring(neighborhood).len())
+            .ok_or_else(|| {
+                PatternError::new(
+                    symmetries.1,
+                    &format!("unsupported symmetries {:?}", symmetries.0),
+                )
+            })?;
+
+        let mut table = RuleTable {
+            name: name.unwrap_or_default(),
+            states: n_states,
+            neighborhood,
+            orders,
+            permute,
+            variables,
+            transitions: transitions.into_iter().map(|(_, t)| t).collect(),
+            colors: vec![None; n_states as usize],
+        };
+
+        for (n, line) in colors {
+            table.parse_colors(&line, n)?;
+        }
+
+        return Ok(table);
+    }
+
+    // "state r g b", or "r1 g1 b1 r2 g2 b2" for a gradient over the states
+    // after 0.
+    fn parse_colors(&mut self, line: &str, n: usize) -> Result<(), PatternError> {
+        let numbers: Vec<u16> = line
+            .split_whitespace()
+            .map(|v| v.parse())
+            .collect::<Result<_, _>>()
+            .map_err(|_| PatternError::new(n, "expected numbers"))?;
+        let color = |c: &[u16]| -> Result<[u8; 3], PatternError> {
+            if c.iter().any(|c| *c > 255) {
+                return Err(PatternError::new(n, "color components go up to 255"));
+            }
+
+            return Ok([c[0] as u8, c[1] as u8, c[2] as u8]);
+        };
+
+        match numbers.len() {
+            4 => {
+                let state = numbers[0] as usize;
+
+                if state >= self.colors.len() {
+                    return Err(PatternError::new(
+                        n,
+                        &format!("state {} out of range", state),
+                    ));
+                }
+                self.colors[state] = Some(color(&numbers[1..])?);
+            }
+            6 => {
+                let (from, to) = (color(&numbers[..3])?, color(&numbers[3..])?);
+                let steps = (self.states as f32 - 2.0).max(1.0);
+
+                for state in 1..self.states {
+                    let t = (state - 1) as f32 / steps;
+                    let mut c = from;
+
+                    for (c, to) in c.iter_mut().zip(to) {
+                        *c = (*c as f32 + (to as f32 - *c as f32) * t).round() as u8;
+                    }
+                    self.colors[state as usize] = Some(c);
+                }
+            }
+            _ => return Err(PatternError::new(n, "expected \"<state> <r> <g> <b>\"")),
+        }
+
+        return Ok(());
+    }
+
+    /// Wireworld, see `WIREWORLD`.
+    pub fn wireworld() -> RuleTable {
+        return get("WireWorld").unwrap();
+    }
+
+    /// Color set for a state in the `@COLORS` section.
+    pub fn color(&self, state: u8) -> Option<[u8; 3]> {
+        return self.colors.get(state as usize).copied().flatten();
+    }
+
+    /// Next state of a cell, its neighbors given in ring order.
+    pub fn next(&self, cell: u8, neighbors: &[u8]) -> u8 {
+        for transition in &self.transitions {
+            if let Some(state) = self.apply(transition, cell, neighbors) {
+                return state;
+            }
+        }
+
+        return cell;
+    }
+
+    fn apply(&self, transition: &Transition, cell: u8, neighbors: &[u8]) -> Option<u8> {
+        let mut bound = vec![None; self.variables.len()];
+
+        if !self.bind(transition.inputs[0], cell, &mut bound) {
+            return None;
+        }
+
+        if self.permute {
+            let mut used = vec![false; neighbors.len()];
+
+            if !self.assign(&transition.inputs[1..], neighbors, &mut used, &mut bound) {
+                return None;
+            }
+        } else {
+            let start = bound;
+
+            bound = self.orders.iter().find_map(|order| {
+                let mut bound = start.clone();
+                let matched = order
+                    .iter()
+                    .zip(&transition.inputs[1..])
+                    .all(|(i, slot)| self.bind(*slot, neighbors[*i], &mut bound));
+
+                return if matched { Some(bound) } else { None };
+            })?;
+        }
+
+        return match transition.output {
+            Slot::State(state) => Some(state),
+            Slot::Variable(i) => bound[i],
+        };
+    }
+
+    // Matches `slot` to a state, binding its variable the first time.
+    fn bind(&self, slot: Slot, state: u8, bound: &mut [Option<u8>]) -> bool {
+        return match slot {
+            Slot::State(s) => s == state,
+            Slot::Variable(i) => match bound[i] {
+                Some(s) => s == state,
+                None if self.variables[i].contains(&state) => {
+                    bound[i] = Some(state);
+                    true
+                }
+                None => false,
+            },
+        };
+    }
+
+    // Matches the slots to neighbors not used yet, in any order.
+    fn assign(
+        &self,
+        slots: &[Slot],
+        neighbors: &[u8],
+        used: &mut [bool],
+        bound: &mut Vec<Option<u8>>,
+    ) -> bool {
+        let (slot, rest) = match slots.split_first() {
+            Some(split) => split,
+            None => return true,
+        };
+        let mut tried = Vec::new();
+
+        for i in 0..neighbors.len() {
+            // Neighbors in the same state match the same way.
+            if used[i] || tried.contains(&neighbors[i]) {
+                continue;
+            }
+            tried.push(neighbors[i]);
+
+            let mut attempt = bound.clone();
+
+            if self.bind(*slot, neighbors[i], &mut attempt) {
+                used[i] = true;
+
+                if self.assign(rest, neighbors, used, &mut attempt) {
+                    *bound = attempt;
+                    return true;
+                }
+                used[i] = false;
+            }
+        }
+
+        return false;
+    }
+}
+
+/// Ring orders for Golly's symmetries: "none", "permute", "reflect_horizontal",
+/// "rotate<n>" and "rotate<n>reflect", `None` if the ring can't have them.
+fn symmetry_orders(symmetries: &str, len: usize) -> Option<(Vec<Vec<usize>>, bool)> {
+    if symmetries == "permute" {
+        return Some((vec![(0..len).collect()], true));
+    }
+
+    let (rotations, reflect) = match symmetries {
+        "none" => (1, false),
+        "reflect_horizontal" => (1, true),
+        _ => {
+            let rotate = symmetries.strip_prefix("rotate")?;
+            let (n, reflect) = match rotate.strip_suffix("reflect") {
+                Some(n) => (n, true),
+                None => (rotate, false),
+            };
+
+            (n.parse::<usize>().ok()?, reflect)
+        }
+    };
+
+    if rotations == 0 || len % rotations != 0 {
+        return None;
+    }
+
+    let step = len / rotations;
+    let mut orders: Vec<Vec<usize>> = Vec::new();
+
+    for mirrored in [false, true] {
+        if mirrored && !reflect {
+            continue;
+        }
+
+        for r in 0..rotations {
+            let order: Vec<usize> = (0..len)
+                .map(|i| {
+                    let i = if mirrored { (len - i) % len } else { i };
+
+                    return (i + r * step) % len;
+                })
+                .collect();
+
+            if !orders.contains(&order) {
+                orders.push(order);
+            }
+        }
+    }
+
+    return Some((orders, false));
+}
+
+//
+// Board run by a rule table. Results are cached by neighborhood, tables are
+// slow to match but boards repeat the same few neighborhoods.
+//
+#[derive(Clone, Debug)]
+pub struct TableLife {
+    pub state: BoardState,
+    pub table: RuleTable,
+    generation: u64,
+    cache: HashMap<Vec<u8>, u8>,
+}
+
+impl TableLife {
+    pub fn new(state: BoardState, table: RuleTable) -> TableLife {
+        return TableLife {
+            state,
+            table,
+            generation: 0,
+            cache: HashMap::new(),
+        };
+    }
+
+    pub fn generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    pub fn tick(&mut self) {
+        let ring = ring(self.table.neighborhood);
+        let mut cells = self.state.cells.clone();
+        let mut key = vec![0; ring.len() + 1];
+
+        for y in 0..self.state.height {
+            for x in 0..self.state.width {
+                key[0] = self.state.get_index(x, y);
+                for (k, (dx, dy)) in ring.iter().enumerate() {
+                    key[k + 1] = self.state.get_wrapped(x + dx, y + dy);
+                }
+
+                let next = match self.cache.get(&key) {
+                    Some(next) => *next,
+                    None => {
+                        let next = self.table.next(key[0], &key[1..]);
+                        self.cache.insert(key.clone(), next);
+                        next
+                    }
+                };
+
+                cells[(y * self.state.width + x) as usize] = next;
+            }
+        }
+
+        self.state.cells = cells;
+        self.generation += 1;
+    }
+
+    pub fn step(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.tick();
+        }
+    }
+}
+
+#[test]
+fn wireworld_runs() {
+    use crate::rle;
+
+    for name in names() {
+        assert!(get(name).is_some());
+    }
+
+    let wireworld = get("wireworld").unwrap();
+    assert_eq!(
+        (wireworld.name.as_str(), wireworld.states),
+        ("WireWorld", 4)
+    );
+    assert_eq!(wireworld.color(1), Some([0, 128, 255]));
+
+    // Wire lights up next to one or two heads, not three.
+    assert_eq!(wireworld.next(3, &[1, 0, 0, 0, 0, 0, 0, 3]), 1);
+    assert_eq!(wireworld.next(3, &[0, 0, 2, 1, 3, 1, 0, 0]), 1);
+    assert_eq!(wireworld.next(3, &[1, 1, 0, 0, 0, 0, 0, 1]), 3);
+    assert_eq!(wireworld.next(1, &[3; 8]), 2);
+    assert_eq!(wireworld.next(2, &[1; 8]), 3);
+    assert_eq!(wireworld.next(0, &[1; 8]), 0);
+
+    // An electron runs down a wire, a tail behind its head.
+    let wire = rle::parse("x = 8, y = 1\nBA6C!").unwrap().state;
+    let mut game = TableLife::new(wire, RuleTable::wireworld());
+    game.step(3);
+    assert_eq!(game.generation(), 3);
+    assert_eq!(game.state.cells, vec![3, 3, 3, 2, 1, 3, 3, 3]);
+
+    // A diode lets electrons through left to right only.
+    let reaches = |wire: &str, x: i32| {
+        let text = format!("x = 9, y = 3\n4b2C${}$4b2C!", wire);
+        let mut game = TableLife::new(rle::parse(&text).unwrap().state, RuleTable::wireworld());
+
+        for _ in 0..10 {
+            game.tick();
+            if game.state.get_index(x, 1) == 1 {
+                return true;
+            }
+        }
+
+        return false;
+    };
+    assert!(reaches("BA3Cb3C", 8));
+    assert!(!reaches("5CbABC", 0));
+}
+
+#[test]
+fn rule_tables_parse() {
+    // Rotations: a cell with a single live orthogonal neighbor is born.
+    let table = RuleTable::parse(
+        "@RULE Test\n@TABLE\nn_states:2\nneighborhood:vonNeumann\nsymmetries:rotate4\n\
+         var a={0,1}\n010001\n1,a,a,a,a,a\n",
+    )
+    .unwrap();
+    assert_eq!(table.next(0, &[1, 0, 0, 0]), 1);
+    assert_eq!(table.next(0, &[0, 0, 1, 0]), 1);
+    assert_eq!(table.next(0, &[1, 0, 1, 0]), 0);
+    // Variables stand for the same state throughout a line.
+    assert_eq!(table.next(1, &[1, 1, 1, 1]), 1);
+    assert_eq!(table.next(1, &[1, 0, 1, 1]), 1);
+
+    let table = RuleTable::parse(
+        "@TABLE\nn_states:3\nneighborhood:Moore\nsymmetries:none\n\
+         var a={1,2}\n0,a,0,0,0,0,0,0,0,a\n@COLORS\n0 0 0 0\n0 0 0 255 255 255\n",
+    )
+    .unwrap();
+    assert_eq!(table.next(0, &[2, 0, 0, 0, 0, 0, 0, 0]), 2);
+    assert_eq!(table.next(0, &[0, 2, 0, 0, 0, 0, 0, 0]), 0);
+    assert_eq!(table.color(0), Some([0, 0, 0]));
+    assert_eq!(table.color(2), Some([255, 255, 255]));
+
+    let ring_orders = |symmetries, len| symmetry_orders(symmetries, len).unwrap().0.len();
+    assert_eq!(ring_orders("rotate8reflect", 8), 16);
+    assert_eq!(ring_orders("rotate4", 8), 4);
+    assert_eq!(ring_orders("rotate6reflect", 6), 12);
+    assert_eq!(ring_orders("reflect_horizontal", 4), 2);
+    assert!(symmetry_orders("rotate3", 8).is_none());
+
+    let error = |text: &str| RuleTable::parse(text).unwrap_err();
+    assert_eq!(
+        error("@TABLE\nn_states:2\nneighborhood:Moore\n0,1,0\n").line,
+        4
+    );
+    assert_eq!(
+        error("@TABLE\nn_states:2\nneighborhood:Moore\nsymmetries:none\n0000000003\n").line,
+        5
+    );
+    assert_eq!(
+        error("@TABLE\nn_states:2\nvar a={0,1}\n0,0,0,0,0,0,0,0,0,b\n").line,
+        4
+    );
+    assert_eq!(error("@TABLE\nneighborhood:Moore\n").line, 1);
+    assert_eq!(
+        error("@TABLE\nn_states:2\nsymmetries:rotate5\n").message,
+        "unsupported symmetries \"rotate5\""
+    );
+}
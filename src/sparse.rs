@@ -0,0 +1,181 @@
+#![allow(dead_code)]
+
+use std::collections::{HashMap, HashSet};
+
+use crate::life::{BoardState, BoundingBox, Shape, DEAD, LIVE};
+use crate::rule::Rule;
+
+//
+// Unbounded plane that only stores live cells.
+//
+#[derive(Clone, Debug)]
+pub struct SparseLife {
+    pub rule: Rule,
+    cells: HashSet<(i64, i64)>,
+    generation: u64,
+}
+
+impl SparseLife {
+    pub fn new(rule: Rule) -> SparseLife {
+        // With B0 the infinite dead background would come alive.
+        assert!(!rule.births(0), "sparse boards do not support B0 rules");
+        assert!(
+            rule.states() == 2,
+            "sparse boards do not support Generations or colored rules"
+        );
+
+        return SparseLife {
+            rule,
+            cells: HashSet::new(),
+            generation: 0,
+        };
+    }
+
+    /// Live cells of a board, with the board's top-left corner at (x, y).
+    pub fn from_state(state: &BoardState, rule: Rule, x: i64, y: i64) -> SparseLife {
+        let mut game = SparseLife::new(rule);
+
+        for (i, cell) in state.cells.iter().enumerate() {
+            if *cell == LIVE {
+                let cx = (i as i32 % state.width) as i64;
+                let cy = (i as i32 / state.width) as i64;
+
+                game.cells.insert((x + cx, y + cy));
+            }
+        }
+
+        return game;
+    }
+
+    pub fn generation(&self) -> u64 {
+        return self.generation;
+    }
+
+    pub fn population(&self) -> usize {
+        return self.cells.len();
+    }
+
+    pub fn get(&self, x: i64, y: i64) -> u8 {
+        return if self.cells.contains(&(x, y)) {
+            LIVE
+        } else {
+            DEAD
+        };
+    }
+
+    pub fn set(&mut self, x: i64, y: i64, cell: u8) {
+        if cell == LIVE {
+            self.cells.insert((x, y));
+        } else {
+            self.cells.remove(&(x, y));
+        }
+    }
+
+    pub fn cells(&self) -> impl Iterator<Item = &(i64, i64)> {
+        return self.cells.iter();
+    }
+
+    pub fn tick(&mut self) {
+        let offsets = self.rule.neighbors();
+        let mut neighbors: HashMap<(i64, i64), u32> = HashMap::with_capacity(self.cells.len() * 4);
+
+        // A live cell at offset (dx, dy) of a cell adds its weight to it.
+        for (x, y) in &self.cells {
+            for ((dx, dy), weight) in &offsets {
+                let cell = (x - *dx as i64, y - *dy as i64);
+
+                *neighbors.entry(cell).or_insert(0) += weight;
+            }
+        }
+
+        let mut next = HashSet::with_capacity(self.cells.len());
+
+        // Cells without live neighbors only matter if they survive with 0.
+        if self.rule.survives(0) {
+            for cell in &self.cells {
+                if !neighbors.contains_key(cell) {
+                    next.insert(*cell);
+                }
+            }
+        }
+
+        for (cell, neighborhood) in neighbors {
+            let current = if self.cells.contains(&cell) {
+                LIVE
+            } else {
+                DEAD
+            };
+
+            if self.rule.next(current, neighborhood) == LIVE {
+                next.insert(cell);
+            }
+        }
+
+        self.cells = next;
+        self.generation += 1;
+    }
+
+    pub fn step(&mut self, generations: u64) {
+        for _ in 0..generations {
+            self.tick();
+        }
+    }
+
+    /// Smallest rectangle holding every live cell, `None` when empty.
+    pub fn bounding_box(&self) -> Option<BoundingBox> {
+        let mut cells = self.cells.iter();
+        let (x, y) = *cells.next()?;
+        let (mut min_x, mut min_y, mut max_x, mut max_y) = (x, y, x, y);
+
+        for (x, y) in cells {
+            min_x = min_x.min(*x);
+            min_y = min_y.min(*y);
+            max_x = max_x.max(*x);
+            max_y = max_y.max(*y);
+        }
+
+        return Some(BoundingBox {
+            x: min_x,
+            y: min_y,
+            width: max_x - min_x + 1,
+            height: max_y - min_y + 1,
+        });
+    }
+
+    /// Copies the window of the plane with top-left corner (x, y) into a
+    /// board.
+    pub fn to_board_state(&self, x: i64, y: i64, width: i32, height: i32) -> BoardState {
+        let mut state = BoardState::new(width, height, Shape::Empty);
+
+        for (cx, cy) in &self.cells {
+            let (bx, by) = (cx - x, cy - y);
+
+            if bx >= 0 && by >= 0 && bx < width as i64 && by < height as i64 {
+                state.cells[(by * width as i64 + bx) as usize] = LIVE;
+            }
+        }
+
+        return state;
+    }
+}
+
+#[test]
+fn glider_never_hits_an_edge() {
+    let glider = BoardState::new(3, 3, Shape::Glider);
+    let mut game = SparseLife::from_state(&glider, Rule::conway(), 0, 0);
+
+    // A glider moves one cell diagonally every 4 generations.
+    game.step(4 * 100);
+
+    assert_eq!(game.population(), 5);
+    assert_eq!(
+        game.bounding_box(),
+        Some(BoundingBox {
+            x: 100,
+            y: 100,
+            width: 3,
+            height: 3
+        })
+    );
+    assert_eq!(game.to_board_state(100, 100, 3, 3).cells, glider.cells);
+}
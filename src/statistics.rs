@@ -0,0 +1,252 @@
+#![allow(dead_code)]
+
+use std::fmt;
+
+use crate::life::{BoardState, BoundingBox, LIVE};
+use crate::rule::Rule;
+
+//
+// Figures of one generation. `births` and `deaths` count the cells that
+// changed from the previous generation. `bounding_box` has a width of 0 on
+// an empty board.
+//
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Statistics {
+    pub generation: u64,
+    pub population: u64,
+    pub births: u64,
+    pub deaths: u64,
+    /// Live cells of each color of Immigration and QuadLife, `populations[0]`
+    /// for color 1. Every live cell is color 1 under other rules.
+    pub populations: [u64; 4],
+    pub bounding_box: BoundingBox,
+    /// Live cells per cell of the board, 0 to 1.
+    pub density: f64,
+}
+
+impl Statistics {
+    /// Figures of a board on its own, without births or deaths.
+    pub fn of(state: &BoardState, rule: &Rule, generation: u64) -> Statistics {
+        let mut statistics = Statistics::default();
+
+        for (i, cell) in state.cells.iter().enumerate() {
+            if rule.is_alive(*cell) {
+                statistics.add_live(i as i32 % state.width, i as i32 / state.width, *cell);
+            }
+        }
+
+        statistics.finish(generation, state.cells.len());
+
+        return statistics;
+    }
+
+    /// Figures of `next`, with births and deaths counted against `previous`.
+    /// Both boards must be the same size.
+    pub fn between(previous: &BoardState, next: &BoardState, rule: &Rule) -> Statistics {
+        let mut statistics = Statistics::default();
+
+        for (i, (before, after)) in previous.cells.iter().zip(&next.cells).enumerate() {
+            if before != after {
+                statistics.add_change(rule.is_alive(*before), rule.is_alive(*after));
+            }
+
+            if rule.is_alive(*after) {
+                statistics.add_live(i as i32 % next.width, i as i32 / next.width, *after);
+            }
+        }
+
+        return statistics;
+    }
+
+    /// Counts a live cell of the new generation, `cell` being its color.
+    pub fn add_live(&mut self, x: i32, y: i32, cell: u8) {
+        let (x, y) = (x as i64, y as i64);
+        let bounds = &mut self.bounding_box;
+
+        if self.population == 0 {
+            *bounds = BoundingBox {
+                x,
+                y,
+                width: 1,
+                height: 1,
+            };
+        } else {
+            let right = (bounds.x + bounds.width).max(x + 1);
+            let bottom = (bounds.y + bounds.height).max(y + 1);
+
+            bounds.x = bounds.x.min(x);
+            bounds.y = bounds.y.min(y);
+            bounds.width = right - bounds.x;
+            bounds.height = bottom - bounds.y;
+        }
+
+        self.population += 1;
+        self.populations[(cell.max(LIVE) as usize - 1).min(3)] += 1;
+    }
+
+    /// Counts a cell that changed between generations, alive or not before
+    /// and after. Under Generations rules a cell dies when it starts
+    /// decaying, later decay steps aren't counted.
+    pub fn add_change(&mut self, alive: bool, next_alive: bool) {
+        if next_alive && !alive {
+            self.births += 1;
+        } else if alive && !next_alive {
+            self.deaths += 1;
+        }
+    }
+
+    /// Adds up the figures of two parts of the same board, e.g. the chunks of
+    /// the tick workers.
+    pub fn merge(&mut self, other: &Statistics) {
+        if other.population > 0 {
+            let (a, b) = (self.bounding_box, other.bounding_box);
+
+            self.bounding_box = if self.population == 0 {
+                b
+            } else {
+                let x = a.x.min(b.x);
+                let y = a.y.min(b.y);
+
+                BoundingBox {
+                    x,
+                    y,
+                    width: (a.x + a.width).max(b.x + b.width) - x,
+                    height: (a.y + a.height).max(b.y + b.height) - y,
+                }
+            };
+        }
+
+        self.population += other.population;
+        for (population, other) in self.populations.iter_mut().zip(other.populations) {
+            *population += other;
+        }
+        self.births += other.births;
+        self.deaths += other.deaths;
+    }
+
+    pub fn finish(&mut self, generation: u64, cells: usize) {
+        self.generation = generation;
+        self.density = if cells == 0 {
+            0.0
+        } else {
+            self.population as f64 / cells as f64
+        };
+    }
+
+    /// One line JSON object, e.g. for the websocket.
+    pub fn to_json(self) -> String {
+        let b = &self.bounding_box;
+
+        return format!(
+            "{{\"generation\":{},\"population\":{},\"births\":{},\"deaths\":{},\
+             \"populations\":[{},{},{},{}],\
+             \"bounding_box\":{{\"x\":{},\"y\":{},\"width\":{},\"height\":{}}},\"density\":{}}}",
+            self.generation,
+            self.population,
+            self.births,
+            self.deaths,
+            self.populations[0],
+            self.populations[1],
+            self.populations[2],
+            self.populations[3],
+            b.x,
+            b.y,
+            b.width,
+            b.height,
+            self.density
+        );
+    }
+}
+
+impl fmt::Display for Statistics {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let b = &self.bounding_box;
+
+        write!(
+            f,
+            "Generation {} | Population {} | Births {} | Deaths {} | Box {}x{} at ({}, {}) | Density {:.2}%",
+            self.generation,
+            self.population,
+            self.births,
+            self.deaths,
+            b.width,
+            b.height,
+            b.x,
+            b.y,
+            self.density * 100.0
+        )
+    }
+}
+
+#[test]
+fn statistics_follow_the_board() {
+    use crate::life::{Backend, Life, Shape};
+    use crate::soup::Soup;
+
+    let glider = Life::new(10, 10, Shape::Glider, 1, Rule::conway());
+    let start = glider.statistics();
+    assert_eq!((start.generation, start.population), (0, 5));
+    assert_eq!(
+        start.bounding_box,
+        BoundingBox {
+            x: 0,
+            y: 0,
+            width: 3,
+            height: 3
+        }
+    );
+    assert_eq!(start.density, 0.05);
+
+    // Every backend and worker count agrees with counting the board again.
+    for (n_workers, backend) in [
+        (1, Backend::Dense),
+        (3, Backend::Dense),
+        (1, Backend::BitPacked),
+    ] {
+        let mut game = Life::new_with_backend(
+            64,
+            48,
+            Shape::Soup(Soup::new(3)),
+            n_workers,
+            Rule::conway(),
+            backend,
+        );
+
+        for _ in 0..20 {
+            let previous = game.state().clone();
+            game.tick();
+
+            let statistics = game.statistics();
+            let mut expected = Statistics::between(&previous, game.state(), &Rule::conway());
+            expected.finish(game.generation(), game.state().cells.len());
+
+            assert_eq!(statistics, expected);
+            assert_eq!(
+                Statistics::of(game.state(), &Rule::conway(), game.generation()).population,
+                statistics.population
+            );
+            assert_eq!(
+                previous.cells.iter().filter(|c| **c == LIVE).count() as u64 + statistics.births
+                    - statistics.deaths,
+                statistics.population
+            );
+        }
+    }
+
+    let mut blinker = Life::new(5, 5, Shape::Blinker, 1, Rule::conway());
+    blinker.tickle();
+    let statistics = blinker.statistics();
+    assert_eq!((statistics.births, statistics.deaths), (2, 2));
+    assert_eq!(
+        (
+            statistics.bounding_box.width,
+            statistics.bounding_box.height
+        ),
+        (3, 1)
+    );
+    assert!(statistics.to_json().contains("\"population\":3,"));
+    assert!(statistics
+        .to_string()
+        .starts_with("Generation 1 | Population 3 | Births 2 | Deaths 2 | Box 3x1 at (1, 2)"));
+}
@@ -0,0 +1,231 @@
+use std::fmt;
+
+//
+// How the edges of a board are glued together when looking up neighbors.
+//
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Topology {
+    /// Cells outside the board are always dead.
+    #[default]
+    Bounded,
+    /// Both pairs of opposite edges are joined.
+    Torus,
+    /// Left and right edges are joined, top and bottom are bounded.
+    HorizontalCylinder,
+    /// Top and bottom edges are joined, left and right are bounded.
+    VerticalCylinder,
+    /// Both pairs of edges are joined, the given pair with a flip.
+    KleinBottle(Twist),
+    /// Both pairs of edges are joined with a flip (projective plane).
+    CrossSurface,
+}
+
+//
+// Which pair of edges of a Klein bottle is joined with a flip.
+//
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Twist {
+    /// Top and bottom edges, crossing them mirrors the x coordinate.
+    Horizontal,
+    /// Left and right edges, crossing them mirrors the y coordinate.
+    Vertical,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseTopologyError {
+    spec: String,
+    reason: String,
+}
+
+impl fmt::Display for ParseTopologyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid bounded grid {:?}: {}", self.spec, self.reason)
+    }
+}
+
+impl std::error::Error for ParseTopologyError {}
+
+impl Topology {
+    pub fn wraps_x(&self) -> bool {
+        return !matches!(self, Topology::Bounded | Topology::VerticalCylinder);
+    }
+
+    pub fn wraps_y(&self) -> bool {
+        return !matches!(self, Topology::Bounded | Topology::HorizontalCylinder);
+    }
+
+    /// Crossing the left/right edges mirrors the y coordinate.
+    fn flips_y(&self) -> bool {
+        return matches!(
+            self,
+            Topology::KleinBottle(Twist::Vertical) | Topology::CrossSurface
+        );
+    }
+
+    /// Crossing the top/bottom edges mirrors the x coordinate.
+    fn flips_x(&self) -> bool {
+        return matches!(
+            self,
+            Topology::KleinBottle(Twist::Horizontal) | Topology::CrossSurface
+        );
+    }
+
+    /// Maps a coordinate, possibly off the board, to the board cell it
+    /// refers to. Returns `None` when it falls off a bounded edge.
+    pub fn map(&self, x: i32, y: i32, width: i32, height: i32) -> Option<(i32, i32)> {
+        let (mut x, mut y) = (x, y);
+
+        if x < 0 || x >= width {
+            if !self.wraps_x() {
+                return None;
+            }
+
+            // Every full crossing of the left/right edges mirrors y once.
+            if self.flips_y() && x.div_euclid(width) % 2 != 0 {
+                y = height - 1 - y;
+            }
+            x = x.rem_euclid(width);
+        }
+
+        if y < 0 || y >= height {
+            if !self.wraps_y() {
+                return None;
+            }
+
+            if self.flips_x() && y.div_euclid(height) % 2 != 0 {
+                x = width - 1 - x;
+            }
+            y = y.rem_euclid(height);
+        }
+
+        return Some((x, y));
+    }
+
+    /// Parses a Golly bounded grid spec such as "P80,38" (plane), "T80,38"
+    /// (torus), "K80*,38" / "K80,38*" (Klein bottle, the asterisk marks the
+    /// twisted pair of edges) or "C80,38" (cross-surface) into the board
+    /// width, height and topology.
+    pub fn parse_golly(spec: &str) -> Result<(i32, i32, Topology), ParseTopologyError> {
+        let error = |reason: &str| ParseTopologyError {
+            spec: spec.to_string(),
+            reason: reason.to_string(),
+        };
+
+        let text = spec.trim();
+        let mut chars = text.chars();
+        let kind = chars.next().ok_or_else(|| error("empty spec"))?;
+        let sizes: Vec<&str> = chars.as_str().split(',').collect();
+
+        if sizes.len() != 2 {
+            return Err(error("expected \"<type><width>,<height>\""));
+        }
+
+        let parse_size = |size: &str| -> Result<(i32, bool), ParseTopologyError> {
+            let twisted = size.ends_with('*');
+            let digits = size.trim_end_matches('*');
+
+            return match digits.parse::<i32>() {
+                Ok(n) if n > 0 => Ok((n, twisted)),
+                Ok(_) => Err(error("unbounded grids are not supported")),
+                Err(_) => Err(error("invalid size")),
+            };
+        };
+
+        let (width, width_twisted) = parse_size(sizes[0])?;
+        let (height, height_twisted) = parse_size(sizes[1])?;
+
+        if width as i64 * height as i64 > i32::MAX as i64 {
+            return Err(error("grid too large for a board"));
+        }
+
+        let topology = match kind.to_ascii_uppercase() {
+            'P' => Topology::Bounded,
+            'T' => Topology::Torus,
+            'C' => Topology::CrossSurface,
+            'K' => match (width_twisted, height_twisted) {
+                (true, false) => Topology::KleinBottle(Twist::Horizontal),
+                (false, true) => Topology::KleinBottle(Twist::Vertical),
+                _ => return Err(error("Klein bottle needs exactly one twisted edge")),
+            },
+            _ => return Err(error("unknown grid type")),
+        };
+
+        if !kind.eq_ignore_ascii_case(&'K') && (width_twisted || height_twisted) {
+            return Err(error("only Klein bottles have twisted edges"));
+        }
+
+        return Ok((width, height, topology));
+    }
+
+    /// Golly bounded grid spec for a board of the given size. Cylinders have
+    /// no finite Golly equivalent.
+    pub fn to_golly(self, width: i32, height: i32) -> Option<String> {
+        return match self {
+            Topology::Bounded => Some(format!("P{},{}", width, height)),
+            Topology::Torus => Some(format!("T{},{}", width, height)),
+            Topology::KleinBottle(Twist::Horizontal) => Some(format!("K{}*,{}", width, height)),
+            Topology::KleinBottle(Twist::Vertical) => Some(format!("K{},{}*", width, height)),
+            Topology::CrossSurface => Some(format!("C{},{}", width, height)),
+            Topology::HorizontalCylinder | Topology::VerticalCylinder => None,
+        };
+    }
+}
+
+#[test]
+fn map_wraps_edges() {
+    assert_eq!(Topology::Bounded.map(-1, 0, 4, 3), None);
+    assert_eq!(Topology::Torus.map(-1, 3, 4, 3), Some((3, 0)));
+    assert_eq!(Topology::HorizontalCylinder.map(4, 1, 4, 3), Some((0, 1)));
+    assert_eq!(Topology::HorizontalCylinder.map(0, -1, 4, 3), None);
+    assert_eq!(Topology::VerticalCylinder.map(1, -1, 4, 3), Some((1, 2)));
+    assert_eq!(Topology::VerticalCylinder.map(-1, 1, 4, 3), None);
+}
+
+#[test]
+fn map_twisted_edges() {
+    let klein = Topology::KleinBottle(Twist::Horizontal);
+    assert_eq!(klein.map(-1, 1, 4, 3), Some((3, 1)));
+    assert_eq!(klein.map(0, -1, 4, 3), Some((3, 2)));
+    assert_eq!(klein.map(1, 3, 4, 3), Some((2, 0)));
+
+    let klein = Topology::KleinBottle(Twist::Vertical);
+    assert_eq!(klein.map(-1, 0, 4, 3), Some((3, 2)));
+    assert_eq!(klein.map(1, 3, 4, 3), Some((1, 0)));
+
+    assert_eq!(Topology::CrossSurface.map(-1, -1, 4, 3), Some((0, 0)));
+    assert_eq!(Topology::CrossSurface.map(4, 0, 4, 3), Some((0, 2)));
+}
+
+#[test]
+fn parse_golly_specs() {
+    assert_eq!(
+        Topology::parse_golly("T80,38"),
+        Ok((80, 38, Topology::Torus))
+    );
+    assert_eq!(
+        Topology::parse_golly("K80*,38"),
+        Ok((80, 38, Topology::KleinBottle(Twist::Horizontal)))
+    );
+    assert_eq!(
+        Topology::parse_golly("k80,38*"),
+        Ok((80, 38, Topology::KleinBottle(Twist::Vertical)))
+    );
+    assert_eq!(
+        Topology::parse_golly("C80,38"),
+        Ok((80, 38, Topology::CrossSurface))
+    );
+    assert_eq!(
+        Topology::parse_golly("P10,5"),
+        Ok((10, 5, Topology::Bounded))
+    );
+
+    assert!(Topology::parse_golly("K80,38").is_err());
+    assert!(Topology::parse_golly("T80*,38").is_err());
+    assert!(Topology::parse_golly("T80,0").is_err());
+    assert!(Topology::parse_golly("X80,38").is_err());
+
+    for spec in ["T80,38", "K80*,38", "K80,38*", "C80,38", "P80,38"] {
+        let (width, height, topology) = Topology::parse_golly(spec).unwrap();
+        assert_eq!(topology.to_golly(width, height).unwrap(), spec);
+    }
+}
@@ -0,0 +1,215 @@
+#![allow(dead_code)]
+
+use crate::life::{BoardState, DEAD};
+
+//
+// The 8 rotations and reflections of a pattern. Rotations are clockwise.
+//
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Transform {
+    #[default]
+    Identity,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+    /// Mirrored left to right.
+    FlipHorizontal,
+    /// Mirrored top to bottom.
+    FlipVertical,
+    /// Mirrored along the top-left to bottom-right diagonal.
+    FlipDiagonal,
+    /// Mirrored along the top-right to bottom-left diagonal.
+    FlipAntiDiagonal,
+}
+
+//
+// How stamped cells combine with the cells already on the board.
+//
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Blend {
+    /// Pattern cells replace board cells, dead ones included.
+    #[default]
+    Overwrite,
+    /// Live pattern cells are set, the rest of the board is kept.
+    Or,
+    /// Live pattern cells toggle board cells.
+    Xor,
+    /// Live pattern cells clear board cells.
+    AndNot,
+}
+
+impl Transform {
+    pub const ALL: [Transform; 8] = [
+        Transform::Identity,
+        Transform::Rotate90,
+        Transform::Rotate180,
+        Transform::Rotate270,
+        Transform::FlipHorizontal,
+        Transform::FlipVertical,
+        Transform::FlipDiagonal,
+        Transform::FlipAntiDiagonal,
+    ];
+
+    /// Whether width and height trade places.
+    pub fn swaps_axes(self) -> bool {
+        return matches!(
+            self,
+            Transform::Rotate90
+                | Transform::Rotate270
+                | Transform::FlipDiagonal
+                | Transform::FlipAntiDiagonal
+        );
+    }
+
+    /// Size of a width x height pattern once transformed.
+    pub fn size(self, width: i32, height: i32) -> (i32, i32) {
+        return if self.swaps_axes() {
+            (height, width)
+        } else {
+            (width, height)
+        };
+    }
+
+    /// Where cell (x, y) of a width x height pattern ends up.
+    pub fn apply(self, x: i32, y: i32, width: i32, height: i32) -> (i32, i32) {
+        let (w, h) = (width - 1, height - 1);
+
+        return match self {
+            Transform::Identity => (x, y),
+            Transform::Rotate90 => (h - y, x),
+            Transform::Rotate180 => (w - x, h - y),
+            Transform::Rotate270 => (y, w - x),
+            Transform::FlipHorizontal => (w - x, y),
+            Transform::FlipVertical => (x, h - y),
+            Transform::FlipDiagonal => (y, x),
+            Transform::FlipAntiDiagonal => (h - y, w - x),
+        };
+    }
+}
+
+impl Blend {
+    pub fn apply(self, cell: u8, pattern: u8) -> u8 {
+        return match self {
+            Blend::Overwrite => pattern,
+            Blend::Or if pattern != DEAD => pattern,
+            Blend::Xor if pattern != DEAD => {
+                if cell == DEAD {
+                    pattern
+                } else {
+                    DEAD
+                }
+            }
+            Blend::AndNot if pattern != DEAD => DEAD,
+            _ => cell,
+        };
+    }
+}
+
+impl BoardState {
+    /// Copy of the board rotated or reflected. The topology is kept.
+    pub fn transformed(&self, transform: Transform) -> BoardState {
+        let (width, height) = transform.size(self.width, self.height);
+        let mut cells = vec![DEAD; self.cells.len()];
+
+        for y in 0..self.height {
+            for x in 0..self.width {
+                let (tx, ty) = transform.apply(x, y, self.width, self.height);
+
+                cells[(ty * width + tx) as usize] = self.cells[(y * self.width + x) as usize];
+            }
+        }
+
+        return BoardState {
+            width,
+            height,
+            cells,
+            topology: self.topology,
+        };
+    }
+
+    /// Stamps a transformed pattern onto the board with its top-left corner
+    /// at (x, y). Cells past a glued edge wrap around, past a bounded edge
+    /// are dropped.
+    pub fn stamp(
+        &mut self,
+        pattern: &BoardState,
+        x: i32,
+        y: i32,
+        transform: Transform,
+        blend: Blend,
+    ) {
+        for py in 0..pattern.height {
+            for px in 0..pattern.width {
+                let (dx, dy) = transform.apply(px, py, pattern.width, pattern.height);
+                let target = self.topology.map(x + dx, y + dy, self.width, self.height);
+
+                if let Some((tx, ty)) = target {
+                    let i = (ty * self.width + tx) as usize;
+                    let cell = pattern.cells[(py * pattern.width + px) as usize];
+
+                    self.cells[i] = blend.apply(self.cells[i], cell);
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn transform_and_stamp_patterns() {
+    use crate::life::{Shape, LIVE};
+
+    let glider = BoardState::new(3, 3, Shape::Glider);
+    let mut seen = Vec::new();
+
+    for transform in Transform::ALL {
+        let cells = glider.transformed(transform).cells;
+
+        assert!(
+            !seen.contains(&cells),
+            "{:?} repeats a transform",
+            transform
+        );
+        seen.push(cells);
+    }
+
+    let mut rotated = glider.clone();
+    for _ in 0..4 {
+        rotated = rotated.transformed(Transform::Rotate90);
+    }
+    assert_eq!(rotated.cells, glider.cells);
+
+    let lwss = BoardState::from_rle("x = 5, y = 4\nbo2bo$o4b$o3bo$4o!").unwrap();
+    let turned = lwss.transformed(Transform::Rotate90);
+    assert_eq!((turned.width, turned.height), (4, 5));
+    assert_eq!(
+        turned.cells,
+        lwss.transformed(Transform::FlipDiagonal)
+            .transformed(Transform::FlipHorizontal)
+            .cells
+    );
+
+    // Stamping with a transform matches stamping the transformed copy.
+    let mut a = BoardState::new(10, 10, Shape::Empty);
+    let mut b = BoardState::new(10, 10, Shape::Empty);
+    a.stamp(&lwss, 3, 2, Transform::Rotate270, Blend::Overwrite);
+    b.place(&lwss.transformed(Transform::Rotate270), 3, 2);
+    assert_eq!(a.cells, b.cells);
+
+    let mut board = BoardState::new(4, 1, Shape::Empty);
+    let pair = BoardState::from_rle("x = 2, y = 1\n2o!").unwrap();
+    let gap = BoardState::from_rle("x = 2, y = 1\nbo!").unwrap();
+
+    board.stamp(&pair, 0, 0, Transform::Identity, Blend::Or);
+    board.stamp(&gap, 1, 0, Transform::Identity, Blend::Or);
+    assert_eq!(board.cells, vec![LIVE, LIVE, LIVE, DEAD]);
+
+    board.stamp(&pair, 1, 0, Transform::Identity, Blend::Xor);
+    assert_eq!(board.cells, vec![LIVE, DEAD, DEAD, DEAD]);
+
+    board.stamp(&pair, 2, 0, Transform::Identity, Blend::Xor);
+    board.stamp(&gap, 2, 0, Transform::Identity, Blend::AndNot);
+    assert_eq!(board.cells, vec![LIVE, DEAD, LIVE, DEAD]);
+
+    board.stamp(&gap, 1, 0, Transform::FlipHorizontal, Blend::Overwrite);
+    assert_eq!(board.cells, vec![LIVE, LIVE, DEAD, DEAD]);
+}